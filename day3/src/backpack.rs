@@ -0,0 +1,172 @@
+use crate::rucksack::{priority, Rucksack};
+use std::fmt;
+
+/// A line of input, split into its two compartments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backpack {
+    pub compartments: [Rucksack; 2],
+    /// The item type packed into both compartments.
+    pub duplicate: char,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackpackError {
+    Empty,
+    OddLength(usize),
+    /// An item that is not an ASCII letter, with its (one-based) column.
+    InvalidItem {
+        item: char,
+        column: usize,
+    },
+    NoSharedItem,
+    MultipleSharedItems(Rucksack),
+}
+
+impl fmt::Display for BackpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackpackError::Empty => write!(f, "empty rucksack"),
+            BackpackError::OddLength(len) => {
+                write!(f, "odd number of items ({len}) cannot be split evenly")
+            }
+            BackpackError::InvalidItem { item, column } => {
+                write!(f, "invalid item {item:?} at column {column}")
+            }
+            BackpackError::NoSharedItem => write!(f, "compartments share no item"),
+            BackpackError::MultipleSharedItems(items) => {
+                write!(f, "compartments share more than one item: {items}")
+            }
+        }
+    }
+}
+
+/// A [`BackpackError`] with the (one-based) line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub error: BackpackError,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl TryFrom<&str> for Backpack {
+    type Error = BackpackError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if let Some((i, c)) = value
+            .chars()
+            .enumerate()
+            .find(|(_, c)| priority(*c).is_none())
+        {
+            return Err(BackpackError::InvalidItem {
+                item: c,
+                column: i + 1,
+            });
+        }
+        // all items are ASCII now, so byte length is the item count
+        if value.is_empty() {
+            return Err(BackpackError::Empty);
+        }
+        if !value.len().is_multiple_of(2) {
+            return Err(BackpackError::OddLength(value.len()));
+        }
+
+        let (comp1, comp2) = value.split_at(value.len() / 2);
+        let compartments = [
+            Rucksack::try_from(comp1).unwrap(),
+            Rucksack::try_from(comp2).unwrap(),
+        ];
+        let shared = compartments[0].intersection(&compartments[1]);
+        let duplicate = match shared.len() {
+            0 => return Err(BackpackError::NoSharedItem),
+            1 => shared.single_item().unwrap(),
+            _ => return Err(BackpackError::MultipleSharedItems(shared)),
+        };
+
+        Ok(Backpack {
+            compartments,
+            duplicate,
+        })
+    }
+}
+
+impl Backpack {
+    pub fn items(&self) -> Rucksack {
+        self.compartments[0].union(&self.compartments[1])
+    }
+}
+
+/// Parses every line of the input, stopping at the first invalid one.
+pub fn parse_input(input: &str) -> Result<Vec<Backpack>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Backpack::try_from(line).map_err(|error| ParseError { line: i + 1, error })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backpack_from() {
+        let backpack = Backpack::try_from("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap();
+        assert_eq!(backpack.duplicate, 'p');
+        assert_eq!(backpack.compartments[0].to_string(), "gprtvwJW");
+        assert_eq!(backpack.items().len(), 14);
+    }
+
+    #[test]
+    fn test_backpack_from_invalid() {
+        assert_eq!(Backpack::try_from(""), Err(BackpackError::Empty));
+        assert_eq!(
+            Backpack::try_from("abcab"),
+            Err(BackpackError::OddLength(5))
+        );
+        assert_eq!(
+            Backpack::try_from("ab1b"),
+            Err(BackpackError::InvalidItem {
+                item: '1',
+                column: 3
+            })
+        );
+        assert_eq!(
+            Backpack::try_from("aébé"),
+            Err(BackpackError::InvalidItem {
+                item: 'é',
+                column: 2
+            })
+        );
+        assert_eq!(Backpack::try_from("abcd"), Err(BackpackError::NoSharedItem));
+        assert_eq!(
+            Backpack::try_from("abab"),
+            Err(BackpackError::MultipleSharedItems(
+                Rucksack::try_from("ab").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(parse_input("abcb\nAbAd").unwrap().len(), 2);
+        let error = parse_input("abcb\nabc\nab.b").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                line: 2,
+                error: BackpackError::OddLength(3)
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 2: odd number of items (3) cannot be split evenly"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! The first, quadratic versions of both parts, timed against the bitset ones on
+//! generated rucksacks.
+
+use crate::{divide_pack, part1, part2};
+use day3::rucksack::{self, priority, Rucksack};
+use itertools::Itertools;
+use std::time::Instant;
+
+fn find_duplicate_naive(backpack: &str) -> char {
+    let (comp1, comp2) = divide_pack(backpack);
+
+    for c in comp1.chars() {
+        if comp2.contains(c) {
+            return c;
+        }
+    }
+
+    panic!("invalid backpack");
+}
+
+fn get_badge_naive(group: &[&str]) -> char {
+    for c in group[0].chars() {
+        if group[1].contains(c) && group[2].contains(c) {
+            return c;
+        }
+    }
+
+    panic!("invalid group");
+}
+
+fn part1_naive(input: &str) -> u64 {
+    input
+        .lines()
+        .map(find_duplicate_naive)
+        .filter_map(priority)
+        .map(u64::from)
+        .sum()
+}
+
+fn part2_naive(input: &str) -> u64 {
+    input
+        .lines()
+        .chunks(3)
+        .into_iter()
+        .map(|chunk| get_badge_naive(&chunk.collect::<Vec<_>>()))
+        .filter_map(priority)
+        .map(u64::from)
+        .sum()
+}
+
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Generates `groups` groups of three rucksacks of `size` items each. Every
+/// rucksack has exactly one item shared between its compartments and every
+/// group has exactly one badge.
+fn generate_input(groups: usize, size: usize) -> String {
+    let mut state = 0x2022_1203;
+    let mut input = String::new();
+    let random_item = |state: &mut u64, exclude: Rucksack| loop {
+        let c = rucksack::item((next_random(state) % 52 + 1) as u32).unwrap();
+        if !exclude.contains(c) {
+            return c;
+        }
+    };
+
+    for _ in 0..groups {
+        let badge = random_item(&mut state, Rucksack::new());
+        let mut used = Rucksack::new();
+        used.insert(badge).unwrap();
+        for elf in 0..3 {
+            // every elf of the group gets its own pool of letters, so the badge is the
+            // only item they have in common
+            let mut own: Vec<char> = vec![];
+            while own.len() < 8 {
+                let c = random_item(&mut state, used);
+                used.insert(c).unwrap();
+                own.push(c);
+            }
+            let (left, right) = own.split_at(4);
+            let duplicate = if elf == 0 { badge } else { right[0] };
+            let mut comp1 = vec![duplicate];
+            let mut comp2 = vec![duplicate];
+            if elf != 0 {
+                comp1.push(badge);
+            }
+            while comp1.len() < size / 2 {
+                comp1.push(left[next_random(&mut state) as usize % left.len()]);
+            }
+            while comp2.len() < size / 2 {
+                comp2.push(right[next_random(&mut state) as usize % right.len()]);
+            }
+            input.extend(comp1.iter().rev());
+            input.extend(comp2.iter());
+            input.push('\n');
+        }
+    }
+    input
+}
+
+pub fn bench(groups: usize, size: usize) {
+    let input = generate_input(groups, size);
+    println!("{} rucksacks of {} items", groups * 3, size);
+
+    let start = Instant::now();
+    let naive = part1_naive(&input);
+    println!("part 1 naive:  {naive} {:?}", start.elapsed());
+    let start = Instant::now();
+    let bitset = part1(&input).unwrap();
+    println!("part 1 bitset: {bitset} {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let naive = part2_naive(&input);
+    println!("part 2 naive:  {naive} {:?}", start.elapsed());
+    let start = Instant::now();
+    let bitset = part2(&input, 3).unwrap();
+    println!("part 2 bitset: {bitset} {:?}", start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naive() {
+        assert_eq!(find_duplicate_naive("vJrwpWtwJgWrhcsFMMfFFhFp"), 'p');
+        assert_eq!(
+            get_badge_naive(&[
+                "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+                "ttgJtRGJQctTZtZT",
+                "CrZsJsPPZsGzwwsLwLmpwMDw"
+            ]),
+            'Z'
+        );
+    }
+
+    #[test]
+    fn test_generate_input() {
+        let input = generate_input(100, 40);
+        assert_eq!(input.lines().count(), 300);
+        assert_eq!(part1(&input), Ok(part1_naive(&input)));
+        assert_eq!(part2(&input, 3), Ok(part2_naive(&input)));
+    }
+}
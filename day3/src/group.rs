@@ -0,0 +1,166 @@
+use crate::rucksack::Rucksack;
+use std::fmt;
+
+/// Reasons why a group of rucksacks has no badge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadgeError {
+    EmptyGroup,
+    IncompleteGroup { expected: usize, found: usize },
+    InvalidItem,
+    NoSharedItem,
+    MultipleSharedItems(Rucksack),
+}
+
+impl fmt::Display for BadgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadgeError::EmptyGroup => write!(f, "empty group"),
+            BadgeError::IncompleteGroup { expected, found } => write!(
+                f,
+                "incomplete group: expected {expected} rucksacks, found {found}"
+            ),
+            BadgeError::InvalidItem => write!(f, "invalid item"),
+            BadgeError::NoSharedItem => write!(f, "no item shared by the whole group"),
+            BadgeError::MultipleSharedItems(items) => {
+                write!(f, "multiple items shared by the whole group: {items}")
+            }
+        }
+    }
+}
+
+/// A [`BadgeError`] together with the (zero-based) index of the group it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupError {
+    pub group: usize,
+    pub error: BadgeError,
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "group {}: {}", self.group + 1, self.error)
+    }
+}
+
+/// Items carried by every rucksack of the group.
+pub fn shared_items(group: &[&str]) -> Result<Rucksack, BadgeError> {
+    group
+        .iter()
+        .map(|x| Rucksack::try_from(*x).map_err(|_| BadgeError::InvalidItem))
+        .reduce(|acc, x| Ok(acc?.intersection(&x?)))
+        .unwrap_or(Err(BadgeError::EmptyGroup))
+}
+
+/// The single item carried by every rucksack of the group.
+pub fn find_badge(group: &[&str]) -> Result<char, BadgeError> {
+    let shared = shared_items(group)?;
+    match shared.len() {
+        0 => Err(BadgeError::NoSharedItem),
+        1 => Ok(shared.single_item().unwrap()),
+        _ => Err(BadgeError::MultipleSharedItems(shared)),
+    }
+}
+
+/// Splits the input into groups of `group_size` lines and finds the badge of each of
+/// them. All failing groups are reported.
+pub fn find_badges(input: &str, group_size: usize) -> Result<Vec<char>, Vec<GroupError>> {
+    if group_size == 0 {
+        return Err(vec![GroupError {
+            group: 0,
+            error: BadgeError::EmptyGroup,
+        }]);
+    }
+
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut badges = vec![];
+    let mut errors = vec![];
+
+    for (i, group) in lines.chunks(group_size).enumerate() {
+        let badge = if group.len() < group_size {
+            Err(BadgeError::IncompleteGroup {
+                expected: group_size,
+                found: group.len(),
+            })
+        } else {
+            find_badge(group)
+        };
+
+        match badge {
+            Ok(c) => badges.push(c),
+            Err(error) => errors.push(GroupError { group: i, error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(badges)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn test_find_badge() {
+        assert_eq!(find_badge(&["abc", "cde", "efc"]), Ok('c'));
+        assert_eq!(find_badge(&["abc", "cde", "efc", "xyzc", "c"]), Ok('c'));
+        assert_eq!(
+            find_badge(&["aBc"]),
+            Err(BadgeError::MultipleSharedItems(
+                Rucksack::try_from("aBc").unwrap()
+            ))
+        );
+        assert_eq!(find_badge(&["abc", "def"]), Err(BadgeError::NoSharedItem));
+        assert_eq!(find_badge(&["ab", "bc", "ba"]), Ok('b'));
+        assert_eq!(find_badge(&["ab", "b?"]), Err(BadgeError::InvalidItem));
+        assert_eq!(find_badge(&[]), Err(BadgeError::EmptyGroup));
+    }
+
+    #[test]
+    fn test_find_badges() {
+        assert_eq!(find_badges(TEST_INPUT, 3), Ok(vec!['r', 'Z']));
+        assert_eq!(
+            find_badges(TEST_INPUT, 6),
+            Err(vec![GroupError {
+                group: 0,
+                error: BadgeError::NoSharedItem,
+            }])
+        );
+        let errors = find_badges(TEST_INPUT, 4).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[1],
+            GroupError {
+                group: 1,
+                error: BadgeError::IncompleteGroup {
+                    expected: 4,
+                    found: 2
+                },
+            }
+        );
+        assert_eq!(
+            find_badges(TEST_INPUT, 0).unwrap_err()[0].error,
+            BadgeError::EmptyGroup
+        );
+    }
+
+    #[test]
+    fn test_group_error_display() {
+        let error = GroupError {
+            group: 1,
+            error: BadgeError::MultipleSharedItems(Rucksack::try_from("ab").unwrap()),
+        };
+        assert_eq!(
+            error.to_string(),
+            "group 2: multiple items shared by the whole group: ab"
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod backpack;
+pub mod group;
+pub mod rucksack;
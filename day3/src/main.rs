@@ -1,4 +1,8 @@
-use itertools::Itertools;
+use day3::backpack::{parse_input, ParseError};
+use day3::group::{find_badges, GroupError};
+use day3::rucksack::priority;
+
+mod bench;
 
 fn divide_pack(backpack: &str) -> (&str, &str) {
     backpack.split_at(backpack.len() / 2)
@@ -16,84 +20,81 @@ fn test_divide_pack() {
     );
 }
 
-fn find_duplicate(backpack: &str) -> char {
-    let (comp1, comp2) = divide_pack(backpack);
-
-    for c in comp1.chars() {
-        if comp2.contains(c) {
-            return c;
-        }
-    }
-
-    panic!("invalid backpack");
-}
-
 #[test]
 fn test_find_duplicate() {
-    assert_eq!(find_duplicate("vJrwpWtwJgWrhcsFMMfFFhFp"), 'p');
-    assert_eq!(find_duplicate("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"), 'L');
-}
-
-fn char_to_score(c: char) -> u64 {
-    if c.is_lowercase() {
-        c as u64 - ('a' as u64) + 1
-    } else {
-        c as u64 - ('A' as u64) + 27
-    }
-}
-
-#[test]
-fn test_char_to_score() {
-    assert_eq!(char_to_score('a'), 1);
-    assert_eq!(char_to_score('C'), 29);
-}
-
-fn get_badge(group: &Vec<&str>) -> char {
-    for c in group[0].chars() {
-        if group[1].contains(c) && group[2].contains(c) {
-            return c;
-        }
-    }
-
-    panic!("invalid group");
+    let find_duplicate = |x| day3::backpack::Backpack::try_from(x).map(|b| b.duplicate);
+    assert_eq!(find_duplicate("vJrwpWtwJgWrhcsFMMfFFhFp"), Ok('p'));
+    assert_eq!(find_duplicate("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"), Ok('L'));
 }
 
 #[test]
 fn test_get_badge() {
     assert_eq!(
-        get_badge(&vec![
+        day3::group::find_badge(&[
             "vJrwpWtwJgWrhcsFMMfFFhFp",
             "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
             "PmmdzqPrVvPwwTWBwg"
         ]),
-        'r'
+        Ok('r')
     );
     assert_eq!(
-        get_badge(&vec![
+        day3::group::find_badge(&[
             "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
             "ttgJtRGJQctTZtZT",
             "CrZsJsPPZsGzwwsLwLmpwMDw"
         ]),
-        'Z'
+        Ok('Z')
     );
 }
 
+fn part1(input: &str) -> Result<u64, ParseError> {
+    Ok(parse_input(input)?
+        .iter()
+        .filter_map(|x| priority(x.duplicate))
+        .map(u64::from)
+        .sum())
+}
+
+fn part2(input: &str, group_size: usize) -> Result<u64, Vec<GroupError>> {
+    Ok(find_badges(input, group_size)?
+        .into_iter()
+        .filter_map(priority)
+        .map(u64::from)
+        .sum())
+}
+
 fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).map(|x| x.as_str()) == Some("bench") {
+        // cargo run --release -- bench [groups] [rucksack size]
+        let groups = args.get(2).and_then(|x| x.parse().ok()).unwrap_or(100_000);
+        let size = args.get(3).and_then(|x| x.parse().ok()).unwrap_or(1_000);
+        bench::bench(groups, size);
+        return;
+    }
+
+    // cargo run -- [group size]
+    let group_size = args.get(1).and_then(|x| x.parse().ok()).unwrap_or(3);
     let input = include_str!("input.txt");
 
-    let answer1 = input
-        .lines()
-        .map(|x| find_duplicate(x))
-        .map(|x| char_to_score(x))
-        .sum::<u64>();
-    println!("answer 1: {}", answer1);
+    match part1(input) {
+        Ok(answer1) => println!("answer 1: {}", answer1),
+        Err(error) => eprintln!("{}", error),
+    }
 
-    let answer2 = input
-        .lines()
-        .chunks(3)
-        .into_iter()
-        .map(|chunk| get_badge(&chunk.collect::<Vec<_>>()))
-        .map(|c| char_to_score(c))
-        .sum::<u64>();
-    println!("answer 2: {}", answer2);
+    match part2(input, group_size) {
+        Ok(answer2) => println!("answer 2: {}", answer2),
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}", error);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_all() {
+    let input = include_str!("input.txt");
+    assert_eq!(part1(input), Ok(8072));
+    assert_eq!(part2(input, 3), Ok(2567));
 }
@@ -0,0 +1,150 @@
+use std::fmt;
+
+/// Set of items stored as a bitmask. Bit `n` is set when the item with
+/// priority `n` is present, so bits 1..=52 are used and bit 0 is always clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rucksack {
+    items: u64,
+}
+
+/// Priority of an item: a-z are 1..=26, A-Z are 27..=52.
+pub fn priority(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some(c as u32 - 'a' as u32 + 1),
+        'A'..='Z' => Some(c as u32 - 'A' as u32 + 27),
+        _ => None,
+    }
+}
+
+/// Inverse of [`priority`].
+pub fn item(priority: u32) -> Option<char> {
+    match priority {
+        1..=26 => char::from_u32('a' as u32 + priority - 1),
+        27..=52 => char::from_u32('A' as u32 + priority - 27),
+        _ => None,
+    }
+}
+
+impl TryFrom<&str> for Rucksack {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut rucksack = Rucksack::new();
+        for c in value.chars() {
+            rucksack.insert(c)?;
+        }
+        Ok(rucksack)
+    }
+}
+
+impl Rucksack {
+    pub fn new() -> Self {
+        Rucksack { items: 0 }
+    }
+
+    pub fn insert(&mut self, c: char) -> Result<(), &'static str> {
+        self.items |= 1 << priority(c).ok_or("invalid item")?;
+        Ok(())
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        priority(c).is_some_and(|p| self.items & (1 << p) != 0)
+    }
+
+    pub fn intersection(&self, other: &Rucksack) -> Rucksack {
+        Rucksack {
+            items: self.items & other.items,
+        }
+    }
+
+    pub fn union(&self, other: &Rucksack) -> Rucksack {
+        Rucksack {
+            items: self.items | other.items,
+        }
+    }
+
+    pub fn difference(&self, other: &Rucksack) -> Rucksack {
+        Rucksack {
+            items: self.items & !other.items,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// Items in the rucksack, ordered by priority.
+    pub fn items(&self) -> impl Iterator<Item = char> {
+        let items = self.items;
+        (1..=52)
+            .filter(move |p| items & (1 << p) != 0)
+            .filter_map(item)
+    }
+
+    /// The only item in the rucksack, if there is exactly one.
+    pub fn single_item(&self) -> Option<char> {
+        if self.len() == 1 {
+            item(self.items.trailing_zeros())
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Rucksack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.items() {
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(priority('a'), Some(1));
+        assert_eq!(priority('z'), Some(26));
+        assert_eq!(priority('A'), Some(27));
+        assert_eq!(priority('C'), Some(29));
+        assert_eq!(priority('Z'), Some(52));
+        assert_eq!(priority('1'), None);
+        assert_eq!(item(1), Some('a'));
+        assert_eq!(item(52), Some('Z'));
+        assert_eq!(item(0), None);
+        assert_eq!(item(53), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = Rucksack::try_from("abcXY").unwrap();
+        let b = Rucksack::try_from("bcdYZ").unwrap();
+        assert_eq!(a.intersection(&b).to_string(), "bcY");
+        assert_eq!(a.union(&b).to_string(), "abcdXYZ");
+        assert_eq!(a.difference(&b).to_string(), "aX");
+        assert_eq!(a.len(), 5);
+        assert!(a.contains('X'));
+        assert!(!a.contains('Z'));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_single_item() {
+        let a = Rucksack::try_from("vJrwpWtwJgWr").unwrap();
+        let b = Rucksack::try_from("hcsFMMfFFhFp").unwrap();
+        assert_eq!(a.intersection(&b).single_item(), Some('p'));
+        assert_eq!(a.single_item(), None);
+    }
+
+    #[test]
+    fn test_invalid_item() {
+        assert!(Rucksack::try_from("ab1").is_err());
+    }
+}
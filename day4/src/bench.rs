@@ -0,0 +1,73 @@
+//! Random assignments over a wide range of sections, to time the sweep line against
+//! comparing every pair.
+
+use day4::interval::Interval;
+use day4::sweep::{max_coverage, overlapping_pairs};
+use std::time::Instant;
+
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Generates `count` assignments of up to `max_len` sections within `1..=sections`.
+fn generate_assignments(count: usize, sections: u64, max_len: u64) -> Vec<Interval> {
+    let mut state = 0x2022_1204;
+    (0..count)
+        .map(|_| {
+            let start = next_random(&mut state) % sections + 1;
+            let len = next_random(&mut state) % max_len;
+            Interval::new(start, (start + len).min(sections)).unwrap()
+        })
+        .collect()
+}
+
+fn overlapping_pairs_naive(assignments: &[Interval]) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    for i in 0..assignments.len() {
+        for j in i + 1..assignments.len() {
+            if assignments[i].overlaps(&assignments[j]) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+pub fn bench(count: usize) {
+    let assignments = generate_assignments(count, 1_000_000_000, 1_000);
+    println!("{count} assignments");
+
+    let start = Instant::now();
+    let max = max_coverage(&assignments).unwrap();
+    println!(
+        "max coverage: {} elves at {} {:?}",
+        max.elves,
+        max.sections,
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let pairs = overlapping_pairs(&assignments).len();
+    println!("overlapping pairs sweep: {pairs} {:?}", start.elapsed());
+    let start = Instant::now();
+    let pairs = overlapping_pairs_naive(&assignments).len();
+    println!("overlapping pairs naive: {pairs} {:?}", start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_overlapping_pairs_generated() {
+        let assignments = generate_assignments(2_000, 100_000, 200);
+        assert_eq!(
+            overlapping_pairs(&assignments),
+            overlapping_pairs_naive(&assignments)
+        );
+    }
+}
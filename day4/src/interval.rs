@@ -0,0 +1,272 @@
+use std::fmt;
+
+/// Inclusive range of sections, `start..=end`. Always non-empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    start: u64,
+    end: u64,
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl Interval {
+    pub fn new(start: u64, end: u64) -> Option<Self> {
+        if start <= end {
+            Some(Interval { start, end })
+        } else {
+            None
+        }
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Number of sections in the interval, as a `u128` since there are 2^64 of them in
+    /// `0-18446744073709551615`.
+    pub fn size(&self) -> u128 {
+        u128::from(self.end - self.start) + 1
+    }
+
+    pub fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && self.end >= other.end
+    }
+
+    pub fn contains_section(&self, section: u64) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Overlapping or directly next to each other, e.g. `2-4` and `5-7`.
+    fn touches(&self, other: &Interval) -> bool {
+        self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1)
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// The union of two intervals, if it is a single interval.
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        if self.touches(other) {
+            Interval::new(self.start.min(other.start), self.end.max(other.end))
+        } else {
+            None
+        }
+    }
+
+    /// Sections of `self` that are not in `other`, at most two intervals.
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut ret = vec![];
+        if self.start < other.start {
+            ret.push(Interval::new(self.start, other.start - 1).unwrap());
+        }
+        if self.end > other.end {
+            ret.push(Interval::new(other.end + 1, self.end).unwrap());
+        }
+        ret
+    }
+}
+
+/// Set of sections, stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut intervals = iter.into_iter().collect::<Vec<_>>();
+        intervals.sort_unstable();
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if last.touches(&interval) => {
+                    last.end = last.end.max(interval.end);
+                }
+                _ => merged.push(interval),
+            }
+        }
+        IntervalSet { intervals: merged }
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self
+            .intervals
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The smallest interval containing the whole set.
+    pub fn bounds(&self) -> Option<Interval> {
+        Interval::new(self.intervals.first()?.start, self.intervals.last()?.end)
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        *self = self
+            .intervals
+            .iter()
+            .copied()
+            .chain(std::iter::once(interval))
+            .collect();
+    }
+
+    pub fn contains_section(&self, section: u64) -> bool {
+        // first interval ending at or after the section
+        let i = self.intervals.partition_point(|x| x.end < section);
+        self.intervals
+            .get(i)
+            .is_some_and(|x| x.contains_section(section))
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ret = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            if let Some(x) = a.intersection(&b) {
+                ret.push(x);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        ret.into_iter().collect()
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ret = vec![];
+        for interval in &self.intervals {
+            let mut rest = vec![*interval];
+            for removed in other.iter().filter(|x| x.overlaps(interval)) {
+                rest = rest.iter().flat_map(|x| x.difference(removed)).collect();
+            }
+            ret.append(&mut rest);
+        }
+        ret.into_iter().collect()
+    }
+
+    /// Total number of sections in the set.
+    pub fn covered(&self) -> u128 {
+        self.intervals.iter().map(|x| x.size()).sum()
+    }
+
+    /// Sections of `within` that are not in the set.
+    pub fn gaps(&self, within: Interval) -> IntervalSet {
+        IntervalSet::from_iter([within]).difference(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn interval(start: u64, end: u64) -> Interval {
+        Interval::new(start, end).unwrap()
+    }
+
+    fn set(intervals: &[(u64, u64)]) -> IntervalSet {
+        intervals.iter().map(|x| interval(x.0, x.1)).collect()
+    }
+
+    #[test]
+    fn test_interval_new() {
+        assert_eq!(Interval::new(8, 2), None);
+        assert_eq!(interval(3, 3).size(), 1);
+        assert_eq!(interval(2, 8).size(), 7);
+        assert_eq!(interval(0, u64::MAX).size(), 1 << 64);
+    }
+
+    #[test]
+    fn test_interval_operations() {
+        let a = interval(2, 6);
+        let b = interval(4, 8);
+        assert_eq!(a.intersection(&b), Some(interval(4, 6)));
+        assert_eq!(a.union(&b), Some(interval(2, 8)));
+        assert_eq!(a.difference(&b), vec![interval(2, 3)]);
+        assert_eq!(
+            interval(1, 9).difference(&a),
+            vec![interval(1, 1), interval(7, 9)]
+        );
+        assert_eq!(a.difference(&interval(1, 9)), vec![]);
+
+        // adjacent intervals have a union but no intersection
+        let c = interval(7, 9);
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c), Some(interval(2, 9)));
+        assert_eq!(interval(2, 4).union(&interval(6, 8)), None);
+    }
+
+    #[test]
+    fn test_interval_set_from_iter() {
+        let s = set(&[(5, 7), (1, 2), (3, 4), (6, 10), (20, 25)]);
+        assert_eq!(s.to_string(), "1-10,20-25");
+        assert_eq!(s.covered(), 16);
+        assert_eq!(s.bounds(), Some(interval(1, 25)));
+        assert!(s.contains_section(10));
+        assert!(!s.contains_section(11));
+        assert!(!s.contains_section(26));
+    }
+
+    #[test]
+    fn test_interval_set_operations() {
+        let a = set(&[(1, 5), (10, 15)]);
+        let b = set(&[(4, 11), (14, 20)]);
+        assert_eq!(a.union(&b), set(&[(1, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(4, 5), (10, 11), (14, 15)]));
+        assert_eq!(a.difference(&b), set(&[(1, 3), (12, 13)]));
+        assert_eq!(b.difference(&a), set(&[(6, 9), (16, 20)]));
+
+        let mut c = a.clone();
+        c.insert(interval(6, 9));
+        assert_eq!(c, set(&[(1, 15)]));
+    }
+
+    #[test]
+    fn test_interval_set_gaps() {
+        let s = set(&[(2, 4), (6, 8), (9, 9)]);
+        assert_eq!(s.gaps(interval(1, 12)), set(&[(1, 1), (5, 5), (10, 12)]));
+        assert_eq!(s.gaps(interval(2, 9)), set(&[(5, 5)]));
+        assert!(IntervalSet::new().gaps(interval(1, 3)).covered() == 3);
+    }
+}
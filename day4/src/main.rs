@@ -1,5 +1,10 @@
+use day4::interval::{Interval, IntervalSet};
+use day4::sweep::{max_coverage, overlapping_pairs};
+use std::fmt;
 use std::time::Instant;
 
+mod bench;
+
 #[derive(Debug)]
 struct AssignmentPair {
     elf1: Assignment,
@@ -7,14 +12,26 @@ struct AssignmentPair {
 }
 
 impl TryFrom<&str> for AssignmentPair {
-    type Error = &'static str;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let vals = value.split(",").collect::<Vec<_>>();
+        AssignmentPair::parse(value, &ParseOptions::default())
+    }
+}
+
+impl AssignmentPair {
+    fn parse(value: &str, options: &ParseOptions) -> Result<Self, ParseError> {
+        let vals = value.split(',').collect::<Vec<_>>();
+        if vals.len() != 2 {
+            return Err(ParseError {
+                column: 1,
+                kind: ParseErrorKind::ExpectedTwoAssignments(vals.len()),
+            });
+        }
 
         Ok(AssignmentPair {
-            elf1: Assignment::try_from(*vals.get(0).ok_or("invalid input")?)?,
-            elf2: Assignment::try_from(*vals.get(1).ok_or("invalid input")?)?,
+            elf1: Assignment::parse_at(vals[0], options, 1)?,
+            elf2: Assignment::parse_at(vals[1], options, vals[0].len() + 2)?,
         })
     }
 }
@@ -29,61 +46,218 @@ impl AssignmentPair {
     }
 }
 
-#[derive(Debug)]
-struct Assignment {
-    start: u64,
-    end: u64,
+/// The sections one elf has to clean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Assignment(Interval);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseErrorKind {
+    ExpectedTwoAssignments(usize),
+    ExpectedTwoSections(usize),
+    InvalidSection,
+    Reversed { start: u64, end: u64 },
+    OutOfBounds { section: u64, bounds: Interval },
+}
+
+/// A [`ParseErrorKind`] with the (one-based) column where the problem starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ParseError {
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+        match self.kind {
+            ParseErrorKind::ExpectedTwoAssignments(n) => {
+                write!(f, "expected two comma-separated assignments, found {n}")
+            }
+            ParseErrorKind::ExpectedTwoSections(n) => {
+                write!(f, "expected two dash-separated sections, found {n}")
+            }
+            ParseErrorKind::InvalidSection => write!(f, "invalid section number"),
+            ParseErrorKind::Reversed { start, end } => {
+                write!(f, "assignment {start}-{end} ends before it starts")
+            }
+            ParseErrorKind::OutOfBounds { section, bounds } => {
+                write!(f, "section {section} is outside of {bounds}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ParseOptions {
+    /// Sections that exist, any section is accepted if not set.
+    bounds: Option<Interval>,
+    /// Swap reversed assignments like `8-2` instead of rejecting them.
+    normalize: bool,
 }
 
 impl TryFrom<&str> for Assignment {
-    type Error = &'static str;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let vals = value
-            .split("-")
-            .filter_map(|x| x.parse::<u64>().ok())
-            .collect::<Vec<_>>();
+        Assignment::parse_at(value, &ParseOptions::default(), 1)
+    }
+}
 
-        Ok(Assignment {
-            start: *vals.get(0).ok_or("invalid input")?,
-            end: *vals.get(1).ok_or("invalid input")?,
-        })
+impl Assignment {
+    /// Parses an assignment starting at `column` of its line.
+    fn parse_at(value: &str, options: &ParseOptions, column: usize) -> Result<Self, ParseError> {
+        let vals = value.split('-').collect::<Vec<_>>();
+        if vals.len() != 2 {
+            return Err(ParseError {
+                column,
+                kind: ParseErrorKind::ExpectedTwoSections(vals.len()),
+            });
+        }
+
+        let columns = [column, column + vals[0].len() + 1];
+        let mut sections = [0; 2];
+        for i in 0..2 {
+            // `parse` would also accept a leading `+`
+            if vals[i].is_empty() || !vals[i].bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseError {
+                    column: columns[i],
+                    kind: ParseErrorKind::InvalidSection,
+                });
+            }
+            sections[i] = vals[i].parse().map_err(|_| ParseError {
+                column: columns[i],
+                kind: ParseErrorKind::InvalidSection,
+            })?;
+        }
+
+        if let Some(bounds) = options.bounds {
+            for i in 0..2 {
+                if !bounds.contains_section(sections[i]) {
+                    return Err(ParseError {
+                        column: columns[i],
+                        kind: ParseErrorKind::OutOfBounds {
+                            section: sections[i],
+                            bounds,
+                        },
+                    });
+                }
+            }
+        }
+
+        let [start, end] = sections;
+        Interval::new(start, end)
+            .or_else(|| Interval::new(end, start).filter(|_| options.normalize))
+            .map(Assignment)
+            .ok_or(ParseError {
+                column,
+                kind: ParseErrorKind::Reversed { start, end },
+            })
     }
 }
 
 impl Assignment {
     fn contains(&self, other: &Assignment) -> bool {
-        self.start <= other.start && self.end >= other.end
+        self.0.contains(&other.0)
     }
 
     fn overlaps(&self, other: &Assignment) -> bool {
-        (self.end >= other.start && self.start <= other.end)
-            || (other.end <= self.start && other.start >= self.end)
+        self.0.overlaps(&other.0)
     }
+
+    fn interval(&self) -> Interval {
+        self.0
+    }
+}
+
+/// All sections cleaned by at least one elf.
+fn coverage<'a>(assignments: impl Iterator<Item = &'a Assignment>) -> IntervalSet {
+    assignments.map(Assignment::interval).collect()
 }
 
 fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).map(|x| x.as_str()) == Some("bench") {
+        // cargo run --release -- bench [assignments]
+        bench::bench(args.get(2).and_then(|x| x.parse().ok()).unwrap_or(100_000));
+        return;
+    }
+
+    // cargo run -- [--normalize] [--max-section N]
+    let bounds = match args.iter().position(|x| x == "--max-section") {
+        Some(i) => {
+            let value = args.get(i + 1).map_or("", |x| x.as_str());
+            match value.parse().ok().and_then(|max| Interval::new(1, max)) {
+                Some(bounds) => Some(bounds),
+                None => {
+                    eprintln!("--max-section {value:?}: expected a section number of at least 1");
+                    return;
+                }
+            }
+        }
+        None => None,
+    };
+    let options = ParseOptions {
+        bounds,
+        normalize: args.iter().any(|x| x == "--normalize"),
+    };
     let input = include_str!("input.txt");
 
-    let start = Instant::now();
-    let part1 = input
+    let pairs = input
         .lines()
-        .map(|line| AssignmentPair::try_from(line).unwrap())
-        .map(|pair| pair.is_self_contained())
-        .filter(|result| *result)
-        .count();
+        .enumerate()
+        .map(|(i, line)| AssignmentPair::parse(line, &options).map_err(|error| (i, error)))
+        .collect::<Result<Vec<_>, _>>();
+    let pairs = match pairs {
+        Ok(pairs) => pairs,
+        Err((i, error)) => {
+            eprintln!("line {}: {}", i + 1, error);
+            return;
+        }
+    };
 
+    let start = Instant::now();
+    let part1 = pairs.iter().filter(|pair| pair.is_self_contained()).count();
     println!("answer 1: {part1} {:?}", start.elapsed());
 
     let start = Instant::now();
-    let part2 = input
-        .lines()
-        .map(|line| AssignmentPair::try_from(line).unwrap())
-        .map(|pair| pair.is_overlapping())
-        .filter(|result| *result)
-        .count();
-
+    let part2 = pairs.iter().filter(|pair| pair.is_overlapping()).count();
     println!("answer 2: {part2} {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let covered = coverage(pairs.iter().flat_map(|pair| [&pair.elf1, &pair.elf2]));
+    println!(
+        "covered sections: {} {:?}",
+        covered.covered(),
+        start.elapsed()
+    );
+    match covered.bounds().map(|bounds| covered.gaps(bounds)) {
+        Some(gaps) if !gaps.is_empty() => println!("sections no elf is cleaning: {gaps}"),
+        _ => println!("sections no elf is cleaning: none"),
+    }
+
+    // elf `i` is the second elf of line `i / 2 + 1` if `i` is odd, the first otherwise
+    let start = Instant::now();
+    let assignments = pairs
+        .iter()
+        .flat_map(|pair| [&pair.elf1, &pair.elf2])
+        .map(Assignment::interval)
+        .collect::<Vec<_>>();
+    let elf_name = |i: usize| format!("{}:{}", i / 2 + 1, i % 2 + 1);
+    if let Some(max) = max_coverage(&assignments) {
+        println!(
+            "most redundant sections: {} cleaned by {} elves",
+            max.sections, max.elves
+        );
+    }
+    let overlapping = overlapping_pairs(&assignments);
+    println!(
+        "overlapping elves: {} pairs {:?}",
+        overlapping.len(),
+        start.elapsed()
+    );
+    for (a, b) in overlapping.iter().take(5) {
+        println!("  {} {}", elf_name(*a), elf_name(*b));
+    }
 }
 
 #[cfg(test)]
@@ -127,4 +301,104 @@ mod tests {
         let p = AssignmentPair::try_from("3-4,1-2").unwrap();
         assert_eq!(p.is_overlapping(), false);
     }
+
+    #[test]
+    fn test_assignment_invalid() {
+        let error = |column, kind| Err(ParseError { column, kind });
+        assert_eq!(
+            Assignment::try_from("3-x-5").map(|_| ()),
+            error(1, ParseErrorKind::ExpectedTwoSections(3))
+        );
+        assert_eq!(
+            Assignment::try_from("35").map(|_| ()),
+            error(1, ParseErrorKind::ExpectedTwoSections(1))
+        );
+        assert_eq!(
+            Assignment::try_from("3-x").map(|_| ()),
+            error(3, ParseErrorKind::InvalidSection)
+        );
+        assert_eq!(
+            Assignment::try_from("-5").map(|_| ()),
+            error(1, ParseErrorKind::InvalidSection)
+        );
+        assert_eq!(
+            Assignment::try_from("3-+5").map(|_| ()),
+            error(3, ParseErrorKind::InvalidSection)
+        );
+        assert_eq!(
+            Assignment::try_from("8-2").map(|_| ()),
+            error(1, ParseErrorKind::Reversed { start: 8, end: 2 })
+        );
+    }
+
+    #[test]
+    fn test_assignment_pair_invalid() {
+        let error = |column, kind| Err(ParseError { column, kind });
+        assert_eq!(
+            AssignmentPair::try_from("2-4").map(|_| ()),
+            error(1, ParseErrorKind::ExpectedTwoAssignments(1))
+        );
+        assert_eq!(
+            AssignmentPair::try_from("2-4,6-8,1-1").map(|_| ()),
+            error(1, ParseErrorKind::ExpectedTwoAssignments(3))
+        );
+        assert_eq!(
+            AssignmentPair::try_from("2-4,16-x").map(|_| ()),
+            error(8, ParseErrorKind::InvalidSection)
+        );
+        assert_eq!(
+            AssignmentPair::try_from("2-4,16-8")
+                .unwrap_err()
+                .to_string(),
+            "column 5: assignment 16-8 ends before it starts"
+        );
+    }
+
+    #[test]
+    fn test_assignment_parse_options() {
+        let options = ParseOptions {
+            bounds: Interval::new(1, 99),
+            normalize: true,
+        };
+        let p = AssignmentPair::parse("8-2,3-3", &options).unwrap();
+        assert_eq!(p.elf1.interval(), Interval::new(2, 8).unwrap());
+        assert_eq!(p.is_self_contained(), true);
+        assert_eq!(
+            AssignmentPair::parse("8-2,3-100", &options).map(|_| ()),
+            Err(ParseError {
+                column: 7,
+                kind: ParseErrorKind::OutOfBounds {
+                    section: 100,
+                    bounds: Interval::new(1, 99).unwrap()
+                }
+            })
+        );
+        assert!(AssignmentPair::parse("0-2,3-3", &options).is_err());
+    }
+
+    #[test]
+    fn test_coverage() {
+        let input = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n12-15,17-17";
+        let pairs = input
+            .lines()
+            .map(|line| AssignmentPair::try_from(line).unwrap())
+            .collect::<Vec<_>>();
+        let covered = coverage(pairs.iter().flat_map(|pair| [&pair.elf1, &pair.elf2]));
+        assert_eq!(covered.covered(), 13);
+        assert_eq!(
+            covered.gaps(covered.bounds().unwrap()).to_string(),
+            "10-11,16-16"
+        );
+    }
+
+    #[test]
+    fn test_last_section() {
+        let p = AssignmentPair::try_from("0-18446744073709551615,1-18446744073709551615").unwrap();
+        assert_eq!(p.is_self_contained(), true);
+        let covered = coverage([&p.elf1, &p.elf2].into_iter());
+        assert_eq!(covered.covered(), 1 << 64);
+        let max = max_coverage(&[p.elf1.interval(), p.elf2.interval()]).unwrap();
+        assert_eq!(max.elves, 2);
+        assert_eq!(max.sections, Interval::new(1, u64::MAX).unwrap());
+    }
 }
@@ -0,0 +1,140 @@
+use crate::interval::Interval;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A run of sections all covered by the same number of elves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverage {
+    pub elves: usize,
+    pub sections: Interval,
+}
+
+/// Coverage changes at `section`: +1 where an assignment starts, -1 right after it ends.
+/// Assignments ending on the last section never end.
+fn events(assignments: &[Interval]) -> Vec<(u64, isize)> {
+    let mut events = Vec::with_capacity(assignments.len() * 2);
+    for a in assignments {
+        events.push((a.start(), 1));
+        if let Some(after) = a.end().checked_add(1) {
+            events.push((after, -1));
+        }
+    }
+    events.sort_unstable();
+    events
+}
+
+/// Splits the covered sections into runs with a constant number of elves, in order.
+pub fn coverage_runs(assignments: &[Interval]) -> Vec<Coverage> {
+    let events = events(assignments);
+    let mut runs = vec![];
+    let mut elves: isize = 0;
+
+    let mut i = 0;
+    while i < events.len() {
+        let section = events[i].0;
+        while i < events.len() && events[i].0 == section {
+            elves += events[i].1;
+            i += 1;
+        }
+        if elves > 0 {
+            // without a next event the run goes up to the last section
+            let end = events.get(i).map_or(u64::MAX, |x| x.0 - 1);
+            runs.push(Coverage {
+                elves: elves as usize,
+                sections: Interval::new(section, end).unwrap(),
+            });
+        }
+    }
+    runs
+}
+
+/// The first run of sections covered by the most elves.
+pub fn max_coverage(assignments: &[Interval]) -> Option<Coverage> {
+    coverage_runs(assignments)
+        .into_iter()
+        .rev()
+        .max_by_key(|x| x.elves)
+}
+
+/// All pairs of assignments (by index, lower first) sharing at least one section.
+pub fn overlapping_pairs(assignments: &[Interval]) -> Vec<(usize, usize)> {
+    let mut order = (0..assignments.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&i| assignments[i].start());
+
+    let mut pairs = vec![];
+    // assignments that started already, by end section
+    let mut active: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::new();
+    for i in order {
+        let current = assignments[i];
+        while let Some(Reverse((end, _))) = active.peek() {
+            if *end >= current.start() {
+                break;
+            }
+            active.pop();
+        }
+        for Reverse((_, j)) in active.iter() {
+            pairs.push((i.min(*j), i.max(*j)));
+        }
+        active.push(Reverse((current.end(), i)));
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn intervals(v: &[(u64, u64)]) -> Vec<Interval> {
+        v.iter().map(|x| Interval::new(x.0, x.1).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_coverage_runs() {
+        let a = intervals(&[(2, 4), (3, 6), (8, 8)]);
+        let runs = coverage_runs(&a)
+            .iter()
+            .map(|x| (x.elves, x.sections.start(), x.sections.end()))
+            .collect::<Vec<_>>();
+        assert_eq!(runs, vec![(1, 2, 2), (2, 3, 4), (1, 5, 6), (1, 8, 8)]);
+
+        let a = intervals(&[(1, u64::MAX), (u64::MAX, u64::MAX)]);
+        let runs = coverage_runs(&a)
+            .iter()
+            .map(|x| (x.elves, x.sections.start(), x.sections.end()))
+            .collect::<Vec<_>>();
+        assert_eq!(runs, vec![(1, 1, u64::MAX - 1), (2, u64::MAX, u64::MAX)]);
+    }
+
+    #[test]
+    fn test_max_coverage() {
+        let a = intervals(&[
+            (2, 4),
+            (6, 8),
+            (2, 3),
+            (4, 5),
+            (5, 7),
+            (7, 9),
+            (2, 8),
+            (3, 7),
+        ]);
+        assert_eq!(
+            max_coverage(&a),
+            Some(Coverage {
+                elves: 5,
+                sections: Interval::new(7, 7).unwrap()
+            })
+        );
+        assert_eq!(max_coverage(&[]), None);
+    }
+
+    #[test]
+    fn test_overlapping_pairs() {
+        let a = intervals(&[(2, 4), (6, 8), (4, 5), (9, 9), (1, 10)]);
+        assert_eq!(
+            overlapping_pairs(&a),
+            vec![(0, 2), (0, 4), (1, 4), (2, 4), (3, 4)]
+        );
+    }
+}
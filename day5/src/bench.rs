@@ -0,0 +1,101 @@
+//! Long random programs over many stacks, to time the logged execution against
+//! running them in bulk.
+
+use day5::crane::parse_crane;
+use day5::execution::Execution;
+use day5::instruction::Instruction;
+use day5::storage::{Crate, Storage};
+use std::time::Instant;
+
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Generates a storage of `stacks` stacks with `height` crates each and a valid program
+/// of `moves` instructions moving up to `max_count` crates at once.
+fn generate(
+    stacks: usize,
+    height: usize,
+    moves: usize,
+    max_count: usize,
+) -> (Storage, Vec<Instruction>) {
+    let mut state = 0x2022_1205;
+    let storage = Storage::from_stacks(
+        (0..stacks)
+            .map(|_| {
+                (0..height)
+                    .map(|_| Crate {
+                        id: (b'A' + (next_random(&mut state) % 26) as u8) as char,
+                    })
+                    .collect()
+            })
+            .collect(),
+    );
+
+    // the program only depends on the heights, which are the same for every crane
+    let mut heights = vec![height; stacks];
+    let mut program = Vec::with_capacity(moves);
+    while program.len() < moves {
+        let from = next_random(&mut state) as usize % stacks;
+        let to = next_random(&mut state) as usize % stacks;
+        if heights[from] == 0 {
+            continue;
+        }
+        let count = next_random(&mut state) as usize % heights[from].min(max_count) + 1;
+        heights[from] -= count;
+        heights[to] += count;
+        program.push(Instruction { count, from, to });
+    }
+    (storage, program)
+}
+
+pub fn bench(stacks: usize, moves: usize) {
+    let (storage, program) = generate(stacks, 20, moves, 20);
+    println!("{stacks} stacks, {moves} moves");
+
+    for name in ["9000", "9001"] {
+        let crane = parse_crane(name).unwrap();
+        let start = Instant::now();
+        let mut execution = Execution::new(storage.clone(), parse_crane(name).unwrap());
+        execution.run(&program).unwrap();
+        let elapsed = start.elapsed();
+        println!(
+            "{name} logged: {:?} ({:.0} moves/s)",
+            elapsed,
+            moves as f64 / elapsed.as_secs_f64()
+        );
+
+        let start = Instant::now();
+        let mut bulk = storage.clone();
+        bulk.run(&program, crane.as_ref()).unwrap();
+        let elapsed = start.elapsed();
+        println!(
+            "{name} bulk:   {:?} ({:.0} moves/s)",
+            elapsed,
+            moves as f64 / elapsed.as_secs_f64()
+        );
+        assert_eq!(&bulk, execution.storage());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_generate() {
+        let (storage, program) = generate(50, 5, 10_000, 10);
+        for crane in [parse_crane("9000").unwrap(), parse_crane("9001").unwrap()] {
+            let mut bulk = storage.clone();
+            bulk.run(&program, crane.as_ref()).unwrap();
+            let mut execution = Execution::new(storage.clone(), crane);
+            execution.run(&program).unwrap();
+            assert_eq!(&bulk, execution.storage());
+            assert_eq!(bulk.heights().iter().sum::<usize>(), 250);
+        }
+    }
+}
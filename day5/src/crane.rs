@@ -0,0 +1,178 @@
+use crate::storage::Crate;
+use std::fmt;
+
+/// How a crane puts the crates of a single instruction onto the destination stack.
+pub trait CraneModel: fmt::Debug {
+    /// Takes the moved crates as they were on the source stack and returns them as
+    /// they end up on the destination stack, both bottom-most first.
+    fn arrange(&self, crates: Vec<Crate>) -> Vec<Crate>;
+
+    /// Moves the top `count` crates of `from` onto `to`, for when the moved crates
+    /// don't have to be recorded. Cranes override this when they can move the crates
+    /// without going through a temporary `Vec`.
+    fn move_crates(&self, from: &mut Vec<Crate>, to: &mut Vec<Crate>, count: usize) {
+        let taken = from.split_off(from.len() - count);
+        to.extend(self.arrange(taken));
+    }
+}
+
+/// Moves one crate at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9000;
+
+impl CraneModel for CrateMover9000 {
+    fn arrange(&self, mut crates: Vec<Crate>) -> Vec<Crate> {
+        crates.reverse();
+        crates
+    }
+
+    fn move_crates(&self, from: &mut Vec<Crate>, to: &mut Vec<Crate>, count: usize) {
+        to.extend(from.drain(from.len() - count..).rev());
+    }
+}
+
+/// Moves all crates of an instruction at once.
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9001;
+
+impl CraneModel for CrateMover9001 {
+    fn arrange(&self, crates: Vec<Crate>) -> Vec<Crate> {
+        crates
+    }
+
+    fn move_crates(&self, from: &mut Vec<Crate>, to: &mut Vec<Crate>, count: usize) {
+        to.extend(from.drain(from.len() - count..));
+    }
+}
+
+/// Lifts at most `capacity` crates at once, keeping their order within each lift.
+#[derive(Debug, Clone, Copy)]
+pub struct LimitedCrane {
+    pub capacity: usize,
+}
+
+impl CraneModel for LimitedCrane {
+    fn arrange(&self, crates: Vec<Crate>) -> Vec<Crate> {
+        crates
+            .rchunks(self.capacity.max(1))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Lifts at most `capacity` crates at once like [`LimitedCrane`], but every other lift
+/// gets flipped over on the way.
+#[derive(Debug, Clone, Copy)]
+pub struct AlternatingCrane {
+    pub capacity: usize,
+}
+
+impl CraneModel for AlternatingCrane {
+    fn arrange(&self, crates: Vec<Crate>) -> Vec<Crate> {
+        crates
+            .rchunks(self.capacity.max(1))
+            .enumerate()
+            .flat_map(|(i, lift)| {
+                let mut lift = lift.to_vec();
+                if i % 2 == 1 {
+                    lift.reverse();
+                }
+                lift
+            })
+            .collect()
+    }
+}
+
+/// Crane from its command line name: `9000`, `9001`, `limited:<capacity>` or
+/// `alternating:<capacity>`.
+pub fn parse_crane(name: &str) -> Result<Box<dyn CraneModel>, &'static str> {
+    let (model, capacity) = match name.split_once(':') {
+        Some((model, capacity)) => (
+            model,
+            Some(
+                capacity
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|x| *x > 0)
+                    .ok_or("invalid crane capacity")?,
+            ),
+        ),
+        None => (name, None),
+    };
+
+    match (model, capacity) {
+        ("9000", None) => Ok(Box::new(CrateMover9000)),
+        ("9001", None) => Ok(Box::new(CrateMover9001)),
+        ("limited", Some(capacity)) => Ok(Box::new(LimitedCrane { capacity })),
+        ("alternating", Some(capacity)) => Ok(Box::new(AlternatingCrane { capacity })),
+        _ => Err("unknown crane model"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn crates(ids: &str) -> Vec<Crate> {
+        ids.chars().map(|id| Crate { id }).collect()
+    }
+
+    fn arrange(crane: &dyn CraneModel, ids: &str) -> String {
+        crane.arrange(crates(ids)).iter().map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn test_crate_movers() {
+        assert_eq!(arrange(&CrateMover9000, "ABCDE"), "EDCBA");
+        assert_eq!(arrange(&CrateMover9001, "ABCDE"), "ABCDE");
+    }
+
+    #[test]
+    fn test_move_crates() {
+        let cranes: [&dyn CraneModel; 4] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &LimitedCrane { capacity: 2 },
+            &AlternatingCrane { capacity: 2 },
+        ];
+        for crane in cranes {
+            let mut from = crates("XYABCDE");
+            let mut to = crates("Z");
+            crane.move_crates(&mut from, &mut to, 5);
+            assert_eq!(from, crates("XY"));
+            assert_eq!(to[1..], crane.arrange(crates("ABCDE")));
+        }
+    }
+
+    #[test]
+    fn test_limited_crane() {
+        assert_eq!(arrange(&LimitedCrane { capacity: 2 }, "ABCDE"), "DEBCA");
+        assert_eq!(arrange(&LimitedCrane { capacity: 1 }, "ABCDE"), "EDCBA");
+        assert_eq!(arrange(&LimitedCrane { capacity: 9 }, "ABCDE"), "ABCDE");
+    }
+
+    #[test]
+    fn test_alternating_crane() {
+        assert_eq!(arrange(&AlternatingCrane { capacity: 2 }, "ABCDE"), "DECBA");
+        assert_eq!(arrange(&AlternatingCrane { capacity: 9 }, "ABCDE"), "ABCDE");
+    }
+
+    #[test]
+    fn test_parse_crane() {
+        assert_eq!(
+            format!("{:?}", parse_crane("limited:3").unwrap()),
+            "LimitedCrane { capacity: 3 }"
+        );
+        assert!(parse_crane("9000").is_ok());
+        assert!(parse_crane("9001").is_ok());
+        assert!(parse_crane("alternating:2").is_ok());
+        assert_eq!(parse_crane("limited").unwrap_err(), "unknown crane model");
+        assert_eq!(
+            parse_crane("limited:0").unwrap_err(),
+            "invalid crane capacity"
+        );
+        assert_eq!(parse_crane("9002").unwrap_err(), "unknown crane model");
+    }
+}
@@ -0,0 +1,204 @@
+use crate::crane::CraneModel;
+use crate::instruction::{Instruction, InstructionError};
+use crate::storage::{Crate, Storage};
+
+/// An executed instruction, with enough information to undo and redo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub instruction: Instruction,
+    /// Crates as they were on the source stack, bottom-most first.
+    pub taken: Vec<Crate>,
+    /// Crates as they were put on the destination stack, bottom-most first.
+    pub placed: Vec<Crate>,
+}
+
+/// Runs instructions against a storage, keeping a log of executed moves. The log costs
+/// two allocations per move, [`Storage::run`] is much faster without it.
+#[derive(Debug)]
+pub struct Execution {
+    storage: Storage,
+    crane: Box<dyn CraneModel>,
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl Execution {
+    pub fn new(storage: Storage, crane: Box<dyn CraneModel>) -> Self {
+        Execution {
+            storage,
+            crane,
+            done: vec![],
+            undone: vec![],
+        }
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    /// Executed moves, oldest first.
+    pub fn log(&self) -> &[Move] {
+        &self.done
+    }
+
+    /// Validates and executes an instruction. Invalid instructions leave the storage
+    /// untouched. Executing a new instruction discards the moves available to redo.
+    pub fn execute(&mut self, instruction: Instruction) -> Result<&Move, InstructionError> {
+        let executed = self.storage.execute(instruction, self.crane.as_ref())?;
+
+        self.undone.clear();
+        self.done.push(executed);
+        Ok(self.done.last().unwrap())
+    }
+
+    /// Executes all instructions, stopping at the first invalid one and returning its
+    /// (zero-based) index.
+    pub fn run(&mut self, program: &[Instruction]) -> Result<(), (usize, InstructionError)> {
+        for (i, instruction) in program.iter().enumerate() {
+            self.execute(*instruction).map_err(|e| (i, e))?;
+        }
+        Ok(())
+    }
+
+    /// Like [`Execution::run`], calling `step` with every executed move and the storage
+    /// after it, e.g. to animate the crane.
+    pub fn run_with<F: FnMut(&Move, &Storage)>(
+        &mut self,
+        program: &[Instruction],
+        mut step: F,
+    ) -> Result<(), (usize, InstructionError)> {
+        for (i, instruction) in program.iter().enumerate() {
+            self.execute(*instruction).map_err(|e| (i, e))?;
+            step(self.done.last().unwrap(), &self.storage);
+        }
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Result<&Move, InstructionError> {
+        let last = self.done.pop().ok_or(InstructionError::NothingToUndo)?;
+        let instruction = last.instruction;
+
+        self.storage.take(instruction.to, instruction.count);
+        self.storage.put(instruction.from, &last.taken);
+
+        self.undone.push(last);
+        Ok(self.undone.last().unwrap())
+    }
+
+    pub fn redo(&mut self) -> Result<&Move, InstructionError> {
+        let next = self.undone.pop().ok_or(InstructionError::NothingToRedo)?;
+        let instruction = next.instruction;
+
+        self.storage.take(instruction.from, instruction.count);
+        self.storage.put(instruction.to, &next.placed);
+
+        self.done.push(next);
+        Ok(self.done.last().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001, LimitedCrane};
+    use crate::instruction::parse_program;
+    use crate::storage::tests::TEST_STORAGE;
+    use pretty_assertions::assert_eq;
+
+    const TEST_PROGRAM: &str = "move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn test_execution_run() {
+        let program = parse_program(TEST_PROGRAM).unwrap();
+
+        let mut execution = Execution::new(TEST_STORAGE.clone(), Box::new(CrateMover9000));
+        execution.run(&program).unwrap();
+        assert_eq!(execution.storage().top_crates_pretty(), "CMZ");
+        assert_eq!(execution.log().len(), 4);
+
+        let mut execution = Execution::new(TEST_STORAGE.clone(), Box::new(CrateMover9001));
+        execution.run(&program).unwrap();
+        assert_eq!(execution.storage().top_crates_pretty(), "MCD");
+    }
+
+    #[test]
+    fn test_execution_run_with() {
+        let program = parse_program(TEST_PROGRAM).unwrap();
+        let mut execution = Execution::new(TEST_STORAGE.clone(), Box::new(CrateMover9000));
+        let mut frames = vec![];
+        execution
+            .run_with(&program, |executed, storage| {
+                frames.push(format!("{}\n{}", executed.instruction, storage))
+            })
+            .unwrap();
+
+        assert_eq!(frames.len(), 4);
+        assert_eq!(
+            frames[0],
+            "move 1 from 2 to 1
+[D]        
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 "
+        );
+    }
+
+    #[test]
+    fn test_execution_invalid() {
+        let program = parse_program("move 1 from 2 to 1\nmove 4 from 1 to 3").unwrap();
+        let mut execution = Execution::new(TEST_STORAGE.clone(), Box::new(CrateMover9000));
+        assert_eq!(
+            execution.run(&program),
+            Err((
+                1,
+                InstructionError::NotEnoughCrates {
+                    stack: 1,
+                    available: 3,
+                    requested: 4
+                }
+            ))
+        );
+        assert_eq!(execution.log().len(), 1);
+    }
+
+    #[test]
+    fn test_execution_undo_redo() {
+        let program = parse_program(TEST_PROGRAM).unwrap();
+
+        let cranes: [Box<dyn CraneModel>; 3] = [
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(LimitedCrane { capacity: 2 }),
+        ];
+        for crane in cranes {
+            let mut execution = Execution::new(TEST_STORAGE.clone(), crane);
+            execution.run(&program).unwrap();
+            let done = execution.storage().clone();
+
+            for _ in 0..4 {
+                execution.undo().unwrap();
+            }
+            assert_eq!(execution.undo(), Err(InstructionError::NothingToUndo));
+            assert_eq!(execution.storage(), &*TEST_STORAGE);
+
+            for instruction in &program {
+                assert_eq!(execution.redo().unwrap().instruction, *instruction);
+            }
+            assert_eq!(execution.redo(), Err(InstructionError::NothingToRedo));
+            assert_eq!(execution.storage(), &done);
+        }
+    }
+
+    #[test]
+    fn test_execution_clears_redo() {
+        let program = parse_program(TEST_PROGRAM).unwrap();
+        let mut execution = Execution::new(TEST_STORAGE.clone(), Box::new(CrateMover9000));
+        execution.run(&program[..2]).unwrap();
+        execution.undo().unwrap();
+        execution.execute(program[0]).unwrap();
+        assert_eq!(execution.redo(), Err(InstructionError::NothingToRedo));
+    }
+}
@@ -0,0 +1,146 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+
+lazy_static! {
+    static ref RE: Regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
+}
+
+/// `move <count> from <from> to <to>`. Stacks are zero-based here and one-based in
+/// the puzzle text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionError {
+    Syntax(String),
+    /// One-based, as written in the instruction.
+    NoSuchStack(usize),
+    NotEnoughCrates {
+        stack: usize,
+        available: usize,
+        requested: usize,
+    },
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstructionError::Syntax(s) => write!(f, "invalid instruction {s:?}"),
+            InstructionError::NoSuchStack(stack) => write!(f, "no stack {stack}"),
+            InstructionError::NotEnoughCrates {
+                stack,
+                available,
+                requested,
+            } => write!(
+                f,
+                "cannot move {requested} crates from stack {stack}, it has {available}"
+            ),
+            InstructionError::NothingToUndo => write!(f, "nothing to undo"),
+            InstructionError::NothingToRedo => write!(f, "nothing to redo"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Instruction {
+    type Error = InstructionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let syntax_error = || InstructionError::Syntax(value.to_string());
+        let caps = RE.captures(value.trim_end()).ok_or_else(syntax_error)?;
+        let parse = |i: usize| caps[i].parse::<usize>().map_err(|_| syntax_error());
+        let count = parse(1)?;
+        let from = parse(2)?;
+        let to = parse(3)?;
+
+        Ok(Instruction {
+            count,
+            from: from
+                .checked_sub(1)
+                .ok_or(InstructionError::NoSuchStack(from))?,
+            to: to.checked_sub(1).ok_or(InstructionError::NoSuchStack(to))?,
+        })
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+/// Parses one instruction per line, reporting the (one-based) line of the first error.
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, (usize, InstructionError)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Instruction::try_from(line).map_err(|e| (i + 1, e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_instruction_from() {
+        assert_eq!(
+            Instruction::try_from("move 2 from 1 to 3"),
+            Ok(Instruction {
+                count: 2,
+                from: 0,
+                to: 2
+            })
+        );
+        assert_eq!(
+            Instruction::try_from("move 2 from 1 to 3")
+                .unwrap()
+                .to_string(),
+            "move 2 from 1 to 3"
+        );
+    }
+
+    #[test]
+    fn test_instruction_from_invalid() {
+        assert_eq!(
+            Instruction::try_from("move 2 from 1"),
+            Err(InstructionError::Syntax("move 2 from 1".to_string()))
+        );
+        assert_eq!(
+            Instruction::try_from("move -2 from 1 to 3"),
+            Err(InstructionError::Syntax("move -2 from 1 to 3".to_string()))
+        );
+        assert_eq!(
+            Instruction::try_from("move 2 from 0 to 3"),
+            Err(InstructionError::NoSuchStack(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_program() {
+        assert_eq!(
+            parse_program("move 1 from 2 to 1\nmove 3 from 1 to 3").map(|x| x.len()),
+            Ok(2)
+        );
+        assert_eq!(
+            parse_program("move 1 from 2 to 1\nmove 3 from one to 3"),
+            Err((
+                2,
+                InstructionError::Syntax("move 3 from one to 3".to_string())
+            ))
+        );
+    }
+}
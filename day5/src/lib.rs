@@ -0,0 +1,4 @@
+pub mod crane;
+pub mod execution;
+pub mod instruction;
+pub mod storage;
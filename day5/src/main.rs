@@ -1,173 +1,102 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Storage {
-    stacks: Vec<Stack>,
-}
-
-impl TryFrom<&str> for Storage {
-    type Error = &'static str;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut stacks: Vec<Stack> = vec![];
-
-        let stack_count = (value
-            .lines()
-            .take(1)
-            .map(|x| x.chars().count())
-            .collect::<Vec<_>>()
-            .get(0)
-            .ok_or("invalid input")?
-            + 1)
-            / 4;
-
-        for _ in 0..stack_count {
-            stacks.push(Stack { crates: vec![] });
-        }
-
-        for line in value.lines().rev().skip(1) {
-            for ch in line.chars().skip(1).step_by(4).enumerate() {
-                if ch.1 != ' ' {
-                    let c = Crate { id: ch.1 };
-                    stacks.get_mut(ch.0).ok_or("invalid input")?.crates.push(c);
-                }
-            }
-        }
-
-        Ok(Storage { stacks: stacks })
-    }
-}
-
-impl Storage {
-    fn execute_movement_instruction(
-        &mut self,
-        movement_instruction: &str,
-    ) -> Result<(), &'static str> {
-        let caps = RE.captures(movement_instruction).unwrap();
-        let count = caps[1].parse::<usize>().unwrap();
-        let from = caps[2].parse::<usize>().unwrap() - 1;
-        let to = caps[3].parse::<usize>().unwrap() - 1;
-
-        self.move_crates(count, from, to)?;
-        Ok(())
-    }
-
-    fn move_crates(&mut self, count: usize, from: usize, to: usize) -> Result<(), &'static str> {
-        for _ in 0..count {
-            self.move_crate(from, to)?;
-        }
-        Ok(())
-    }
-
-    fn move_crate(&mut self, from: usize, to: usize) -> Result<(), &'static str> {
-        let moved_crate = self
-            .stacks
-            .get_mut(from)
-            .ok_or("cannot get stack")?
-            .crates
-            .pop()
-            .unwrap();
-        self.stacks
-            .get_mut(to)
-            .ok_or("cannot get stack")?
-            .crates
-            .push(moved_crate);
-        Ok(())
-    }
-
-    fn execute_movement_instruction_9001(
-        &mut self,
-        movement_instruction: &str,
-    ) -> Result<(), &'static str> {
-        let caps = RE.captures(movement_instruction).ok_or("regex error")?;
-        let count = caps[1].parse::<usize>().unwrap();
-        let from = caps[2].parse::<usize>().unwrap() - 1;
-        let to = caps[3].parse::<usize>().unwrap() - 1;
-
-        self.move_crates_9001(count, from, to)?;
-        Ok(())
-    }
-
-    fn move_crates_9001(
-        &mut self,
-        count: usize,
-        from: usize,
-        to: usize,
-    ) -> Result<(), &'static str> {
-        let range = self
-            .stacks
-            .get_mut(from)
-            .ok_or("cannot get stack")?
-            .crates
-            .len()
-            - count..;
-        // remove from old stack
-        let mut moved_crates = self
-            .stacks
-            .get_mut(from)
-            .ok_or("cannot get stack")?
-            .crates
-            .drain(range)
-            .collect::<Vec<_>>();
-        // add to new stack
-        self.stacks[to].crates.append(&mut moved_crates);
-        Ok(())
-    }
-
-    fn get_top_crates(&self) -> Vec<Crate> {
-        let mut ret = vec![];
-        for stack in &self.stacks {
-            ret.push(stack.crates.last().copied().unwrap());
+use day5::crane::{parse_crane, CraneModel, CrateMover9000, CrateMover9001};
+use day5::execution::Execution;
+use day5::instruction::{parse_program, Instruction};
+use day5::storage::{Storage, EMPTY_STACK};
+use std::thread;
+use std::time::Duration;
+
+mod bench;
+
+fn run(
+    storage: &Storage,
+    program: &[Instruction],
+    crane: Box<dyn CraneModel>,
+    animate: bool,
+) -> Storage {
+    if !animate {
+        // no frames to draw, so no need to log the moves
+        let mut storage = storage.clone();
+        if let Err((i, error)) = storage.run(program, crane.as_ref()) {
+            eprintln!("instruction {}: {}", i + 1, error);
         }
-        ret
+        return storage;
     }
 
-    fn top_crates_pretty(&self) -> String {
-        self.get_top_crates()
-            .iter()
-            .fold(String::new(), |acc, x| acc + &x.id.to_string())
+    let mut execution = Execution::new(storage.clone(), crane);
+    let result = execution.run_with(program, |executed, storage| {
+        // clear the terminal and draw the next frame
+        print!("\x1b[2J\x1b[H");
+        println!("{}\n\n{}\n", executed.instruction, storage);
+        thread::sleep(Duration::from_millis(50));
+    });
+    if let Err((i, error)) = result {
+        eprintln!("instruction {}: {}", i + 1, error);
     }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Stack {
-    crates: Vec<Crate>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Crate {
-    id: char,
+    execution.storage().clone()
 }
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(|x| x.as_str()) == Some("bench") {
+        // cargo run --release -- bench [stacks] [moves]
+        let stacks = args.get(1).and_then(|x| x.parse().ok()).unwrap_or(5_000);
+        let moves = args
+            .get(2)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(5_000_000);
+        bench::bench(stacks, moves);
+        return;
+    }
+
     let input = include_str!("input.txt");
     let input_parts = input.split("\n\n").collect::<Vec<_>>();
-    let storage = input_parts[0];
-    let movements = input_parts[1];
-
-    // part 1
-    {
-        let mut storage = Storage::try_from(storage).unwrap();
-        for line in movements.lines() {
-            storage.execute_movement_instruction(line).unwrap();
+    let storage = match Storage::try_from(input_parts[0]) {
+        Ok(storage) => storage,
+        Err(error) => {
+            eprintln!("storage: {error}");
+            return;
         }
-
-        println!("answer 1: {}", storage.top_crates_pretty());
-    }
-    // part 2
-    {
-        let mut storage = Storage::try_from(storage).unwrap();
-        for line in movements.lines() {
-            storage.execute_movement_instruction_9001(line).unwrap();
+    };
+    let program = match parse_program(input_parts[1]) {
+        Ok(program) => program,
+        Err((line, error)) => {
+            eprintln!("instruction {line}: {error}");
+            return;
         }
-
-        println!("answer 2: {}", storage.top_crates_pretty());
+    };
+
+    // cargo run -- [--animate] [--summary] [crane...], e.g. limited:3 alternating:2
+    let animate = args.iter().any(|x| x == "--animate");
+    let summary = args.iter().any(|x| x == "--summary");
+    let report = |name: &str, storage: Storage| {
+        println!("{name}: {}", storage.top_crates_pretty());
+        if summary {
+            println!("{}", storage.summary(EMPTY_STACK));
+        }
+    };
+
+    let cranes = args
+        .iter()
+        .filter(|x| !x.starts_with("--"))
+        .collect::<Vec<_>>();
+    if !cranes.is_empty() {
+        for name in cranes {
+            match parse_crane(name) {
+                Ok(crane) => report(name, run(&storage, &program, crane, animate)),
+                Err(error) => eprintln!("{name}: {error}"),
+            }
+        }
+        return;
     }
+
+    report(
+        "answer 1",
+        run(&storage, &program, Box::new(CrateMover9000), animate),
+    );
+    report(
+        "answer 2",
+        run(&storage, &program, Box::new(CrateMover9001), animate),
+    );
 }
 
 #[cfg(test)]
@@ -175,56 +104,6 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
-    lazy_static! {
-        static ref TEST_STORAGE: Storage = Storage {
-            stacks: vec![
-                Stack {
-                    crates: vec![Crate { id: 'Z' }, Crate { id: 'N' }],
-                },
-                Stack {
-                    crates: vec![Crate { id: 'M' }, Crate { id: 'C' }, Crate { id: 'D' }],
-                },
-                Stack {
-                    crates: vec![Crate { id: 'P' }],
-                },
-            ],
-        };
-    }
-
-    #[test]
-    fn test_storage_from() {
-        let test_str = "    [D]    
-[N] [C]    
-[Z] [M] [P]
- 1   2   3 ";
-        println!("{}", test_str);
-
-        assert_eq!(Storage::try_from(test_str).unwrap(), *TEST_STORAGE);
-    }
-
-    #[test]
-    fn test_storage_execute_movement() {
-        let mut storage = TEST_STORAGE.clone();
-        storage
-            .execute_movement_instruction("move 2 from 2 to 3")
-            .unwrap();
-        assert_eq!(storage.stacks[2].crates.last().unwrap(), &Crate { id: 'C' });
-    }
-
-    #[test]
-    fn test_storage_execute_movement_9001() {
-        let mut storage = TEST_STORAGE.clone();
-        storage
-            .execute_movement_instruction_9001("move 2 from 1 to 3")
-            .unwrap();
-        assert_eq!(storage.stacks[2].crates.last().unwrap(), &Crate { id: 'N' });
-    }
-
-    #[test]
-    fn test_storage_top_crates_pretty() {
-        assert_eq!(TEST_STORAGE.top_crates_pretty(), "NDP");
-    }
-
     #[test]
     fn test_all() {
         let input = include_str!("input.txt");
@@ -236,7 +115,9 @@ mod tests {
         {
             let mut storage = Storage::try_from(storage).unwrap();
             for line in movements.lines() {
-                storage.execute_movement_instruction(line).unwrap();
+                storage
+                    .execute_movement_instruction(line, &CrateMover9000)
+                    .unwrap();
             }
 
             assert_eq!("RLFNRTNFB", storage.top_crates_pretty());
@@ -245,10 +126,47 @@ mod tests {
         {
             let mut storage = Storage::try_from(storage).unwrap();
             for line in movements.lines() {
-                storage.execute_movement_instruction_9001(line).unwrap();
+                storage
+                    .execute_movement_instruction(line, &CrateMover9001)
+                    .unwrap();
             }
 
             assert_eq!("MHQTLJRLB", storage.top_crates_pretty());
         }
     }
+
+    #[test]
+    fn test_storage_round_trip() {
+        let input = include_str!("input.txt");
+        let diagram = input.split("\n\n").next().unwrap();
+        assert_eq!(Storage::try_from(diagram).unwrap().to_string(), diagram);
+    }
+
+    #[test]
+    fn test_all_execution() {
+        let input = include_str!("input.txt");
+        let input_parts = input.split("\n\n").collect::<Vec<_>>();
+        let storage = Storage::try_from(input_parts[0]).unwrap();
+        let program = parse_program(input_parts[1]).unwrap();
+
+        let mut execution = Execution::new(storage.clone(), Box::new(CrateMover9000));
+        execution.run(&program).unwrap();
+        assert_eq!("RLFNRTNFB", execution.storage().top_crates_pretty());
+
+        let mut execution = Execution::new(storage.clone(), Box::new(CrateMover9001));
+        execution.run(&program).unwrap();
+        assert_eq!("MHQTLJRLB", execution.storage().top_crates_pretty());
+
+        // the extreme capacities match the two original models
+        let crane = parse_crane("limited:1").unwrap();
+        assert_eq!(
+            "RLFNRTNFB",
+            run(&storage, &program, crane, false).top_crates_pretty()
+        );
+        let crane = parse_crane("alternating:100").unwrap();
+        assert_eq!(
+            "MHQTLJRLB",
+            run(&storage, &program, crane, false).top_crates_pretty()
+        );
+    }
 }
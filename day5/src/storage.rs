@@ -0,0 +1,628 @@
+use crate::crane::CraneModel;
+use crate::execution::Move;
+use crate::instruction::{Instruction, InstructionError};
+use std::fmt;
+
+/// Shown instead of a crate for empty stacks.
+pub const EMPTY_STACK: char = '-';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+    stacks: Vec<Stack>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageErrorKind {
+    MissingFooter,
+    /// Stacks have to be numbered 1, 2, 3... from left to right.
+    InvalidStackNumber,
+    MalformedCrate,
+    /// A crate that is not above any stack number.
+    MisalignedCrate,
+    /// A crate with an empty space below it.
+    FloatingCrate,
+    UnexpectedCharacter(char),
+}
+
+/// A [`StorageErrorKind`] with its (one-based) position in the diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: StorageErrorKind,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            StorageErrorKind::MissingFooter => write!(f, "missing stack numbers"),
+            StorageErrorKind::InvalidStackNumber => write!(f, "invalid stack number"),
+            StorageErrorKind::MalformedCrate => write!(f, "malformed crate"),
+            StorageErrorKind::MisalignedCrate => write!(f, "crate is not above a stack"),
+            StorageErrorKind::FloatingCrate => write!(f, "crate is floating in the air"),
+            StorageErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected {c:?}"),
+        }
+    }
+}
+
+/// Character ranges of the stack numbers in the footer line, left to right.
+fn parse_footer(footer: &[char], line: usize) -> Result<Vec<(usize, usize)>, StorageError> {
+    let mut labels: Vec<(usize, usize)> = vec![];
+    let mut i = 0;
+    while i < footer.len() {
+        if footer[i] == ' ' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < footer.len() && footer[i] != ' ' {
+            i += 1;
+        }
+        let label = footer[start..i].iter().collect::<String>();
+        if label.parse::<usize>().ok() != Some(labels.len() + 1) {
+            return Err(StorageError {
+                line,
+                column: start + 1,
+                kind: StorageErrorKind::InvalidStackNumber,
+            });
+        }
+        labels.push((start, i));
+    }
+    Ok(labels)
+}
+
+impl TryFrom<&str> for Storage {
+    type Error = StorageError;
+
+    /// Parses the diagram, using the footer to find where stacks are. Lines may be
+    /// ragged and stack numbers can have several digits, crates have to be written
+    /// above their stack's number.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let lines = value
+            .lines()
+            .map(|x| x.trim_end().chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let footer_line = lines
+            .iter()
+            .rposition(|x| !x.is_empty())
+            .ok_or(StorageError {
+                line: 1,
+                column: 1,
+                kind: StorageErrorKind::MissingFooter,
+            })?;
+        let labels = parse_footer(&lines[footer_line], footer_line + 1)?;
+        if labels.is_empty() {
+            return Err(StorageError {
+                line: footer_line + 1,
+                column: 1,
+                kind: StorageErrorKind::MissingFooter,
+            });
+        }
+
+        let mut stacks = vec![Stack { crates: vec![] }; labels.len()];
+        for (i, line) in lines[..footer_line].iter().enumerate().rev() {
+            let error = |column: usize, kind| StorageError {
+                line: i + 1,
+                column: column + 1,
+                kind,
+            };
+            let mut column = 0;
+            while column < line.len() {
+                match line[column] {
+                    ' ' => {
+                        column += 1;
+                        continue;
+                    }
+                    '[' => (),
+                    c => return Err(error(column, StorageErrorKind::UnexpectedCharacter(c))),
+                }
+                let id = match (line.get(column + 1), line.get(column + 2)) {
+                    (Some(&id), Some(']')) if id != ' ' && id != '[' && id != ']' => id,
+                    _ => return Err(error(column, StorageErrorKind::MalformedCrate)),
+                };
+                let stack = labels
+                    .iter()
+                    .position(|(start, end)| (*start..*end).contains(&(column + 1)))
+                    .ok_or_else(|| error(column, StorageErrorKind::MisalignedCrate))?;
+                let level = footer_line - 1 - i;
+                if stacks[stack].crates.len() != level {
+                    return Err(error(column, StorageErrorKind::FloatingCrate));
+                }
+                stacks[stack].crates.push(Crate { id });
+                column += 3;
+            }
+        }
+
+        Ok(Storage { stacks })
+    }
+}
+
+impl fmt::Display for Storage {
+    /// Renders the storage as the puzzle's diagram, which parses back into the same
+    /// storage. Columns are widened to fit stack numbers over 999.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.stacks.len().to_string().len().max(3);
+        let height = self.stacks.iter().map(|x| x.crates.len()).max();
+        for level in (0..height.unwrap_or(0)).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.crates.get(level) {
+                    Some(c) => format!("{:^width$}", format!("[{}]", c.id)),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        let footer = (1..=self.stacks.len())
+            .map(|i| format!("{i:^width$}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", footer.join(" "))
+    }
+}
+
+impl Storage {
+    /// Storage with the given stacks, bottom-most crate first.
+    pub fn from_stacks(stacks: Vec<Vec<Crate>>) -> Self {
+        Storage {
+            stacks: stacks.into_iter().map(|crates| Stack { crates }).collect(),
+        }
+    }
+
+    /// Checks that the instruction can be executed on the storage as it is now.
+    pub fn validate(&self, instruction: &Instruction) -> Result<(), InstructionError> {
+        let from = instruction.from;
+        let available = self
+            .stacks
+            .get(from)
+            .ok_or(InstructionError::NoSuchStack(from + 1))?
+            .crates
+            .len();
+        let to = instruction.to;
+        self.stacks
+            .get(to)
+            .ok_or(InstructionError::NoSuchStack(to + 1))?;
+
+        if available < instruction.count {
+            return Err(InstructionError::NotEnoughCrates {
+                stack: from + 1,
+                available,
+                requested: instruction.count,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn execute_movement_instruction(
+        &mut self,
+        movement_instruction: &str,
+        crane: &dyn CraneModel,
+    ) -> Result<(), InstructionError> {
+        let instruction = Instruction::try_from(movement_instruction)?;
+        self.execute(instruction, crane)?;
+        Ok(())
+    }
+
+    /// Validates and executes an instruction. Invalid instructions leave the storage
+    /// untouched.
+    pub fn execute(
+        &mut self,
+        instruction: Instruction,
+        crane: &dyn CraneModel,
+    ) -> Result<Move, InstructionError> {
+        self.validate(&instruction)?;
+
+        let taken = self.take(instruction.from, instruction.count);
+        let placed = crane.arrange(taken.clone());
+        self.put(instruction.to, &placed);
+
+        Ok(Move {
+            instruction,
+            taken,
+            placed,
+        })
+    }
+
+    /// Like [`Storage::execute`], but without recording the moved crates. Faster for
+    /// long programs, especially with cranes that move crates in bulk.
+    pub fn apply(
+        &mut self,
+        instruction: &Instruction,
+        crane: &dyn CraneModel,
+    ) -> Result<(), InstructionError> {
+        self.validate(instruction)?;
+
+        let (from, to) = (instruction.from, instruction.to);
+        if from == to {
+            let taken = self.take(from, instruction.count);
+            self.put(to, &crane.arrange(taken));
+            return Ok(());
+        }
+
+        // borrow both stacks at once
+        let (low, high) = self.stacks.split_at_mut(from.max(to));
+        let (from, to) = if from < to {
+            (&mut low[from], &mut high[0])
+        } else {
+            (&mut high[0], &mut low[to])
+        };
+        crane.move_crates(&mut from.crates, &mut to.crates, instruction.count);
+        Ok(())
+    }
+
+    /// Applies all instructions, stopping at the first invalid one and returning its
+    /// (zero-based) index. This is the fast way to run a program, use
+    /// [`Execution`](crate::execution::Execution) only to undo moves or watch them.
+    pub fn run(
+        &mut self,
+        program: &[Instruction],
+        crane: &dyn CraneModel,
+    ) -> Result<(), (usize, InstructionError)> {
+        for (i, instruction) in program.iter().enumerate() {
+            self.apply(instruction, crane).map_err(|e| (i, e))?;
+        }
+        Ok(())
+    }
+
+    /// Removes the top `count` crates of a stack, bottom-most first. The caller
+    /// has to make sure that the stack exists and has enough crates.
+    pub(crate) fn take(&mut self, stack: usize, count: usize) -> Vec<Crate> {
+        let crates = &mut self.stacks[stack].crates;
+        crates.split_off(crates.len() - count)
+    }
+
+    /// Puts crates on top of a stack, bottom-most first.
+    pub(crate) fn put(&mut self, stack: usize, crates: &[Crate]) {
+        self.stacks[stack].crates.extend_from_slice(crates);
+    }
+
+    /// Top crate of every stack, `None` for empty stacks.
+    pub fn get_top_crates(&self) -> Vec<Option<Crate>> {
+        self.stacks
+            .iter()
+            .map(|stack| stack.crates.last().copied())
+            .collect()
+    }
+
+    /// Top crates, with `-` for empty stacks.
+    pub fn top_crates_pretty(&self) -> String {
+        self.top_crates_with(EMPTY_STACK)
+    }
+
+    /// Top crates, with `placeholder` for empty stacks.
+    pub fn top_crates_with(&self, placeholder: char) -> String {
+        self.get_top_crates()
+            .iter()
+            .map(|x| x.map_or(placeholder, |c| c.id))
+            .collect()
+    }
+
+    pub fn stack_count(&self) -> usize {
+        self.stacks.len()
+    }
+
+    /// Crates of a (zero-based) stack, bottom-most first.
+    pub fn stack(&self, stack: usize) -> Option<&[Crate]> {
+        self.stacks.get(stack).map(|x| x.crates.as_slice())
+    }
+
+    pub fn heights(&self) -> Vec<usize> {
+        self.stacks.iter().map(|x| x.crates.len()).collect()
+    }
+
+    /// Table with the height, top crate and contents of every stack.
+    pub fn summary(&self, placeholder: char) -> String {
+        let mut ret = String::from("stack  height  top  crates\n");
+        for (i, stack) in self.stacks.iter().enumerate() {
+            let top = stack.crates.last().map_or(placeholder, |c| c.id);
+            let crates = stack.crates.iter().map(|c| c.id).collect::<String>();
+            ret += &format!(
+                "{:>5}  {:>6}  {:>3}  {}\n",
+                i + 1,
+                stack.crates.len(),
+                top,
+                crates
+            );
+        }
+        ret
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    crates: Vec<Crate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crate {
+    pub id: char,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use lazy_static::lazy_static;
+    use pretty_assertions::assert_eq;
+
+    lazy_static! {
+        pub(crate) static ref TEST_STORAGE: Storage = Storage {
+            stacks: vec![
+                Stack {
+                    crates: vec![Crate { id: 'Z' }, Crate { id: 'N' }],
+                },
+                Stack {
+                    crates: vec![Crate { id: 'M' }, Crate { id: 'C' }, Crate { id: 'D' }],
+                },
+                Stack {
+                    crates: vec![Crate { id: 'P' }],
+                },
+            ],
+        };
+    }
+
+    #[test]
+    fn test_storage_from() {
+        let test_str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 ";
+        println!("{}", test_str);
+
+        assert_eq!(Storage::try_from(test_str).unwrap(), *TEST_STORAGE);
+    }
+
+    #[test]
+    fn test_storage_from_ragged() {
+        // no trailing spaces, blank rows above the crates
+        let test_str = "
+           
+    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+";
+        assert_eq!(Storage::try_from(test_str).unwrap(), *TEST_STORAGE);
+    }
+
+    #[test]
+    fn test_storage_from_wide() {
+        let test_str = "                                            [L]
+[A]                                 [J]     [K]
+ 1   2   3   4   5   6   7   8   9  10  11  12";
+        let storage = Storage::try_from(test_str).unwrap();
+        assert_eq!(storage.stacks.len(), 12);
+        assert_eq!(storage.stacks[0].crates, vec![Crate { id: 'A' }]);
+        assert_eq!(storage.stacks[9].crates, vec![Crate { id: 'J' }]);
+        assert_eq!(
+            storage.stacks[11].crates,
+            vec![Crate { id: 'K' }, Crate { id: 'L' }]
+        );
+        // our own rendering puts the numbers differently, but they still line up
+        assert_eq!(
+            Storage::try_from(storage.to_string().as_str()).unwrap(),
+            storage
+        );
+    }
+
+    #[test]
+    fn test_storage_from_invalid() {
+        let error = |line, column, kind| Err(StorageError { line, column, kind });
+        assert_eq!(
+            Storage::try_from("\n  \n"),
+            error(1, 1, StorageErrorKind::MissingFooter)
+        );
+        assert_eq!(
+            Storage::try_from("[A] [B]\n 1   3"),
+            error(2, 6, StorageErrorKind::InvalidStackNumber)
+        );
+        assert_eq!(
+            Storage::try_from("[A] [B]\n 1   x"),
+            error(2, 6, StorageErrorKind::InvalidStackNumber)
+        );
+        assert_eq!(
+            Storage::try_from("  [A]\n 1   2"),
+            error(1, 3, StorageErrorKind::MisalignedCrate)
+        );
+        assert_eq!(
+            Storage::try_from("[A] [B\n 1   2"),
+            error(1, 5, StorageErrorKind::MalformedCrate)
+        );
+        assert_eq!(
+            Storage::try_from("[A] []\n 1   2"),
+            error(1, 5, StorageErrorKind::MalformedCrate)
+        );
+        assert_eq!(
+            Storage::try_from("[A] B\n 1   2"),
+            error(1, 5, StorageErrorKind::UnexpectedCharacter('B'))
+        );
+        assert_eq!(
+            Storage::try_from("    [A]\n[B]    \n 1   2"),
+            error(1, 5, StorageErrorKind::FloatingCrate)
+        );
+        assert_eq!(
+            Storage::try_from("    [A]\n[B]    \n 1   2")
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 5: crate is floating in the air"
+        );
+    }
+
+    #[test]
+    fn test_storage_execute_movement() {
+        let mut storage = TEST_STORAGE.clone();
+        storage
+            .execute_movement_instruction("move 2 from 2 to 3", &CrateMover9000)
+            .unwrap();
+        assert_eq!(storage.stacks[2].crates.last().unwrap(), &Crate { id: 'C' });
+    }
+
+    #[test]
+    fn test_storage_execute_movement_9001() {
+        let mut storage = TEST_STORAGE.clone();
+        storage
+            .execute_movement_instruction("move 2 from 1 to 3", &CrateMover9001)
+            .unwrap();
+        assert_eq!(storage.stacks[2].crates.last().unwrap(), &Crate { id: 'N' });
+    }
+
+    #[test]
+    fn test_storage_execute_movement_invalid() {
+        let mut storage = TEST_STORAGE.clone();
+        assert_eq!(
+            storage.execute_movement_instruction("move 3 from 1 to 2", &CrateMover9000),
+            Err(InstructionError::NotEnoughCrates {
+                stack: 1,
+                available: 2,
+                requested: 3
+            })
+        );
+        assert_eq!(
+            storage.execute_movement_instruction("move 1 from 1 to 4", &CrateMover9001),
+            Err(InstructionError::NoSuchStack(4))
+        );
+        assert_eq!(
+            storage.execute_movement_instruction("move 1 from 7 to 2", &CrateMover9000),
+            Err(InstructionError::NoSuchStack(7))
+        );
+        // nothing was moved
+        assert_eq!(storage, *TEST_STORAGE);
+    }
+
+    #[test]
+    fn test_storage_display() {
+        let test_str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 ";
+        assert_eq!(TEST_STORAGE.to_string(), test_str);
+    }
+
+    #[test]
+    fn test_storage_display_many_stacks() {
+        let stacks = (0..1200)
+            .map(|i| {
+                (0..i % 4)
+                    .map(|j| Crate {
+                        id: (b'A' + ((i + j) % 26) as u8) as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        let storage = Storage::from_stacks(stacks);
+        let diagram = storage.to_string();
+        assert!(diagram.contains("998  999  1000 1001"));
+        assert_eq!(Storage::try_from(diagram.as_str()).unwrap(), storage);
+    }
+
+    #[test]
+    fn test_storage_display_round_trip() {
+        let mut storage = TEST_STORAGE.clone();
+        for instruction in [
+            "move 1 from 2 to 1",
+            "move 3 from 1 to 3",
+            "move 1 from 3 to 2",
+        ] {
+            storage
+                .execute_movement_instruction(instruction, &CrateMover9000)
+                .unwrap();
+            assert_eq!(
+                Storage::try_from(storage.to_string().as_str()).unwrap(),
+                storage
+            );
+        }
+        // the first stack is empty now
+        assert_eq!(
+            storage.to_string(),
+            "    [Z] [N]
+    [C] [D]
+    [M] [P]
+ 1   2   3 "
+        );
+    }
+
+    #[test]
+    fn test_storage_apply() {
+        let program = [
+            "move 1 from 2 to 1",
+            "move 3 from 1 to 3",
+            "move 2 from 2 to 1",
+            "move 1 from 1 to 2",
+            "move 2 from 3 to 3",
+        ]
+        .map(|x| Instruction::try_from(x).unwrap());
+
+        let cranes: [&dyn CraneModel; 2] = [&CrateMover9000, &CrateMover9001];
+        for crane in cranes {
+            let mut expected = TEST_STORAGE.clone();
+            for instruction in &program {
+                expected.execute(*instruction, crane).unwrap();
+            }
+            let mut storage = TEST_STORAGE.clone();
+            storage.run(&program, crane).unwrap();
+            assert_eq!(storage, expected);
+        }
+
+        let mut storage = TEST_STORAGE.clone();
+        let invalid = Instruction::try_from("move 3 from 3 to 1").unwrap();
+        assert_eq!(
+            storage.run(&[program[0], invalid], &CrateMover9000),
+            Err((
+                1,
+                InstructionError::NotEnoughCrates {
+                    stack: 3,
+                    available: 1,
+                    requested: 3
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_storage_top_crates_pretty() {
+        assert_eq!(TEST_STORAGE.top_crates_pretty(), "NDP");
+    }
+
+    #[test]
+    fn test_storage_top_crates_empty_stack() {
+        let mut storage = TEST_STORAGE.clone();
+        storage
+            .execute_movement_instruction("move 2 from 1 to 3", &CrateMover9001)
+            .unwrap();
+        assert_eq!(
+            storage.get_top_crates(),
+            vec![None, Some(Crate { id: 'D' }), Some(Crate { id: 'N' })]
+        );
+        assert_eq!(storage.top_crates_pretty(), "-DN");
+        assert_eq!(storage.top_crates_with(' '), " DN");
+    }
+
+    #[test]
+    fn test_storage_queries() {
+        assert_eq!(TEST_STORAGE.stack_count(), 3);
+        assert_eq!(TEST_STORAGE.heights(), vec![2, 3, 1]);
+        assert_eq!(
+            TEST_STORAGE.stack(0),
+            Some(&[Crate { id: 'Z' }, Crate { id: 'N' }][..])
+        );
+        assert_eq!(TEST_STORAGE.stack(3), None);
+    }
+
+    #[test]
+    fn test_storage_summary() {
+        let mut storage = TEST_STORAGE.clone();
+        storage
+            .execute_movement_instruction("move 1 from 3 to 1", &CrateMover9000)
+            .unwrap();
+        assert_eq!(
+            storage.summary('.'),
+            "stack  height  top  crates
+    1       3    P  ZNP
+    2       3    D  MCD
+    3       0    .  
+"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! The first version of the marker search, checking every window from scratch, timed
+//! against the incremental and bitmask ones.
+
+use day6::bitmask::find_marker_fast;
+use day6::marker::find_marker;
+use std::collections::{HashSet, VecDeque};
+use std::time::Instant;
+
+fn check_duplicates(v: &VecDeque<char>) -> bool {
+    let mut set = HashSet::new();
+    for c in v {
+        if set.contains(c) {
+            return true;
+        }
+        set.insert(c);
+    }
+    false
+}
+
+fn find_seq_start(s: &str, contig_count: usize) -> usize {
+    let mut buf: VecDeque<char> = VecDeque::new();
+
+    for c in s.chars().enumerate() {
+        // init
+        if buf.len() < contig_count {
+            buf.push_back(c.1);
+        } else {
+            if !check_duplicates(&buf) {
+                return c.0;
+            }
+            buf.pop_front();
+            buf.push_back(c.1);
+        }
+    }
+    0
+}
+
+/// `len` characters cycling through the first `window - 1` letters, so there is no
+/// marker until a `z` close to the end.
+fn generate_stream(len: usize, window: usize) -> String {
+    let mut stream = (b'a'..=b'y')
+        .take(window - 1)
+        .cycle()
+        .take(len - 2)
+        .map(char::from)
+        .collect::<String>();
+    // one more character after the marker, the original version checks one late
+    stream.push_str("zz");
+    stream
+}
+
+pub fn bench(len: usize) {
+    for window in [4, 14] {
+        let stream = generate_stream(len, window);
+        println!("{len} characters, window {window}");
+
+        let start = Instant::now();
+        let naive = find_seq_start(&stream, window);
+        println!("naive:       {naive} {:?}", start.elapsed());
+        let start = Instant::now();
+        let incremental = find_marker(&stream, window).unwrap();
+        println!("incremental: {incremental} {:?}", start.elapsed());
+        let start = Instant::now();
+        let bitmask = find_marker_fast(&stream, window).unwrap();
+        println!("bitmask:     {bitmask} {:?}", start.elapsed());
+
+        // not lowercase, so this falls back to the incremental version right away
+        let stream = stream.to_uppercase();
+        let start = Instant::now();
+        let fallback = find_marker_fast(&stream, window).unwrap();
+        println!("fallback:    {fallback} {:?}", start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_find_seq_start() {
+        // size - 4
+        assert_eq!(find_seq_start("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), 5);
+        assert_eq!(find_seq_start("nppdvjthqldpwncqszvftbrmjlhg", 4), 6);
+        assert_eq!(find_seq_start("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4), 10);
+        assert_eq!(find_seq_start("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4), 11);
+
+        // size - 14
+        assert_eq!(find_seq_start("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), 19);
+        assert_eq!(find_seq_start("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), 23);
+        assert_eq!(find_seq_start("nppdvjthqldpwncqszvftbrmjlhg", 14), 23);
+        assert_eq!(find_seq_start("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14), 29);
+        assert_eq!(find_seq_start("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), 26);
+    }
+
+    #[test]
+    fn test_check_duplicates() {
+        let v1 = VecDeque::from(['a', 'b', 'c', 'd']);
+        assert_eq!(check_duplicates(&v1), false);
+
+        let v2 = VecDeque::from(['a', 'b', 'c', 'c']);
+        assert_eq!(check_duplicates(&v2), true);
+
+        let v3 = VecDeque::from(['a', 'a', 'c', 'c']);
+        assert_eq!(check_duplicates(&v3), true);
+    }
+
+    #[test]
+    fn test_generate_stream() {
+        for window in [4, 14] {
+            let stream = generate_stream(1000, window);
+            assert_eq!(stream.len(), 1000);
+            assert_eq!(find_marker(&stream, window), Some(999));
+            assert_eq!(find_seq_start(&stream, window), 999);
+        }
+    }
+
+    #[test]
+    fn test_find_seq_start_input() {
+        let input = include_str!("input.txt").trim();
+        assert_eq!(find_seq_start(input, 4), 1109);
+        assert_eq!(find_seq_start(input, 14), 3965);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::marker::find_marker;
+use std::fmt;
+
+/// The stream has a byte that does not fit in the 26-bit mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedByte {
+    pub position: usize,
+    pub byte: u8,
+}
+
+impl fmt::Display for UnsupportedByte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "byte {:#04x} at {} is not a lowercase ASCII letter",
+            self.byte, self.position
+        )
+    }
+}
+
+fn bit(byte: u8, position: usize) -> Result<u32, UnsupportedByte> {
+    if byte.is_ascii_lowercase() {
+        Ok(1 << (byte - b'a'))
+    } else {
+        Err(UnsupportedByte { position, byte })
+    }
+}
+
+/// Like [`find_marker`], for streams of lowercase ASCII letters only. The window is
+/// kept as a mask where bit `n` is set when letter `n` is in the window an odd number
+/// of times: adding and removing a letter are both a xor, and the window is a marker
+/// when it has `window` bits set.
+pub fn find_marker_lowercase(
+    bytes: &[u8],
+    window: usize,
+) -> Result<Option<usize>, UnsupportedByte> {
+    if window == 0 {
+        return Ok(Some(0));
+    }
+    if window > 26 {
+        // cannot have that many different letters, only check the input
+        return match bytes.iter().position(|x| !x.is_ascii_lowercase()) {
+            Some(position) => Err(UnsupportedByte {
+                position,
+                byte: bytes[position],
+            }),
+            None => Ok(None),
+        };
+    }
+
+    let mut mask: u32 = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        mask ^= bit(byte, i)?;
+        if i >= window {
+            // already checked when it was added
+            mask ^= 1 << (bytes[i - window] - b'a');
+        }
+        if mask.count_ones() as usize == window {
+            return Ok(Some(i + 1));
+        }
+    }
+    Ok(None)
+}
+
+/// [`find_marker_lowercase`] when the stream allows it, [`find_marker`] otherwise.
+pub fn find_marker_fast(s: &str, window: usize) -> Option<usize> {
+    // a non-ASCII byte means positions in bytes and characters differ, so start over
+    find_marker_lowercase(s.as_bytes(), window).unwrap_or_else(|_| find_marker(s, window))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_find_marker_lowercase() {
+        let streams = [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+            "aaaaaaa",
+            "abc",
+            "",
+        ];
+        for stream in streams {
+            for window in [0, 1, 2, 4, 14, 26, 27] {
+                assert_eq!(
+                    find_marker_lowercase(stream.as_bytes(), window),
+                    Ok(find_marker(stream, window)),
+                    "{stream} {window}"
+                );
+            }
+        }
+        let alphabet = "abcdefghijklmnopqrstuvwxyz";
+        assert_eq!(find_marker_lowercase(alphabet.as_bytes(), 26), Ok(Some(26)));
+    }
+
+    #[test]
+    fn test_unsupported_byte() {
+        assert_eq!(
+            find_marker_lowercase(b"abcAbc", 4),
+            Err(UnsupportedByte {
+                position: 3,
+                byte: b'A'
+            })
+        );
+        assert_eq!(
+            find_marker_lowercase(b"abc\n", 30),
+            Err(UnsupportedByte {
+                position: 3,
+                byte: b'\n'
+            })
+        );
+        // the marker is found before the unsupported byte
+        assert_eq!(find_marker_lowercase(b"abcdE", 4), Ok(Some(4)));
+    }
+
+    #[test]
+    fn test_find_marker_fast() {
+        assert_eq!(find_marker_fast("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), Some(5));
+        assert_eq!(find_marker_fast("ééàèé", 3), Some(4));
+        assert_eq!(find_marker_fast("AABCD", 4), Some(5));
+    }
+}
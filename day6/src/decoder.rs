@@ -0,0 +1,217 @@
+use crate::marker::MarkerDetector;
+use crate::stream::MarkerKind;
+use std::fmt;
+
+/// A packet or message: a marker followed by its payload, which runs until the next
+/// marker of the same kind or the end of the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// Offset of the first byte of the marker.
+    pub marker: usize,
+    /// Offset of the first byte of the payload.
+    pub offset: usize,
+    pub payload: &'a [u8],
+}
+
+impl Frame<'_> {
+    pub fn len(&self) -> usize {
+        self.payload.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payload.is_empty()
+    }
+}
+
+/// End of the first marker starting at or after `start`.
+fn find_marker_from(stream: &[u8], start: usize, window: usize) -> Option<usize> {
+    let mut detector = MarkerDetector::new(window);
+    stream[start..]
+        .iter()
+        .position(|b| detector.push(*b))
+        .map(|i| start + i + 1)
+}
+
+/// Splits a stream into frames delimited by markers of one kind.
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    stream: &'a [u8],
+    window: usize,
+    /// End of the marker of the next frame.
+    next: Option<usize>,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(stream: &'a [u8], kind: MarkerKind) -> Self {
+        let window = kind.window();
+        Decoder {
+            stream,
+            window,
+            next: find_marker_from(stream, 0, window),
+        }
+    }
+
+    /// Bytes before the first marker, the whole stream if there is no marker.
+    pub fn preamble(&self) -> &'a [u8] {
+        let end = find_marker_from(self.stream, 0, self.window)
+            .map_or(self.stream.len(), |x| x - self.window);
+        &self.stream[..end]
+    }
+}
+
+impl<'a> Iterator for Decoder<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.next?;
+        // markers don't overlap, the next one starts looking after this one
+        self.next = find_marker_from(self.stream, offset, self.window);
+        let end = self.next.map_or(self.stream.len(), |x| x - self.window);
+
+        Some(Frame {
+            marker: offset - self.window,
+            offset,
+            payload: &self.stream[offset..end],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The payload contains a marker at the given offset, so it would be split.
+    PayloadContainsMarker { payload: usize, offset: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::PayloadContainsMarker { payload, offset } => {
+                write!(f, "payload {payload} contains a marker at offset {offset}")
+            }
+        }
+    }
+}
+
+/// Builds a stream that [`Decoder`] splits back into `payloads`. Payloads may not
+/// contain a marker themselves.
+pub fn encode(payloads: &[&[u8]], kind: MarkerKind) -> Result<Vec<u8>, EncodeError> {
+    let window = kind.window();
+    let mut stream = vec![];
+
+    for (i, payload) in payloads.iter().enumerate() {
+        if let Some(end) = find_marker_from(payload, 0, window) {
+            return Err(EncodeError::PayloadContainsMarker {
+                payload: i,
+                offset: end - window,
+            });
+        }
+
+        // Repeating the last byte of the previous payload puts a duplicate in every
+        // window overlapping both, so the marker can't be found any earlier.
+        let first = stream.last().copied().unwrap_or(b'a');
+        stream.push(first);
+        stream.extend((b'a'..=b'z').filter(|x| *x != first).take(window - 1));
+        stream.extend_from_slice(payload);
+    }
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_decoder() {
+        let stream = b"xxabcdhelloaaaaxyzwbbbbbbwxyz";
+        let mut decoder = Decoder::new(stream, MarkerKind::StartOfPacket);
+        assert_eq!(decoder.preamble(), b"x");
+        assert_eq!(
+            decoder.next(),
+            Some(Frame {
+                marker: 1,
+                offset: 5,
+                payload: b""
+            })
+        );
+        // "dhel" is a marker, markers never overlap so "abcd" isn't
+        let frames = decoder.collect::<Vec<_>>();
+        assert_eq!(
+            frames.iter().map(|x| x.marker).collect::<Vec<_>>(),
+            vec![5, 14, 24]
+        );
+        assert_eq!(frames[0].payload, b"loaaa");
+        assert_eq!(frames[0].offset, 9);
+        assert_eq!(frames[1].payload, b"wbbbbb");
+        assert_eq!(frames[1].len(), 6);
+        assert_eq!(frames[2].payload, b"z");
+        assert_eq!(frames[2].offset + frames[2].len(), stream.len());
+    }
+
+    #[test]
+    fn test_decoder_no_marker() {
+        let stream = b"aabbaabb";
+        let decoder = Decoder::new(stream, MarkerKind::StartOfPacket);
+        assert_eq!(decoder.preamble(), stream);
+        assert_eq!(decoder.count(), 0);
+    }
+
+    #[test]
+    fn test_encode() {
+        let stream = encode(&[b"aab", b"", b"zzz"], MarkerKind::StartOfPacket).unwrap();
+        assert_eq!(stream, b"abcdaabbacddabczzz");
+        assert_eq!(
+            encode(&[b"aab", b"aabcd"], MarkerKind::StartOfPacket),
+            Err(EncodeError::PayloadContainsMarker {
+                payload: 1,
+                offset: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payloads: [&[u8]; 6] = [
+            b"",
+            b"hello",
+            b"",
+            b"mississippi",
+            b"aaaaaaaaaaaa",
+            b"abcabcabcabcabcabcabcabcabcabcabc",
+        ];
+        for kind in [MarkerKind::StartOfPacket, MarkerKind::StartOfMessage] {
+            // packet payloads can't have four different bytes in a row
+            let payloads = payloads
+                .iter()
+                .filter(|x| find_marker_from(x, 0, kind.window()).is_none())
+                .copied()
+                .collect::<Vec<_>>();
+            let stream = encode(&payloads, kind).unwrap();
+            let decoder = Decoder::new(&stream, kind);
+            assert_eq!(decoder.preamble(), b"");
+            let decoded = decoder.map(|x| x.payload).collect::<Vec<_>>();
+            assert_eq!(decoded, payloads);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_generated() {
+        for round in 0..100 {
+            let payloads = (0..10)
+                .map(|k| {
+                    let len = (round * 7 + k * 13) % 40;
+                    // only three different bytes, so never a marker
+                    (0..len)
+                        .map(|i| b"xyz"[(round + k * i + i * i) % 3])
+                        .collect::<Vec<u8>>()
+                })
+                .collect::<Vec<_>>();
+            let payloads = payloads.iter().map(|x| x.as_slice()).collect::<Vec<_>>();
+            let stream = encode(&payloads, MarkerKind::StartOfPacket).unwrap();
+            let decoded = Decoder::new(&stream, MarkerKind::StartOfPacket)
+                .map(|x| x.payload)
+                .collect::<Vec<_>>();
+            assert_eq!(decoded, payloads);
+        }
+    }
+}
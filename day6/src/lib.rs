@@ -0,0 +1,4 @@
+pub mod bitmask;
+pub mod decoder;
+pub mod marker;
+pub mod stream;
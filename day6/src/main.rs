@@ -1,77 +1,99 @@
-use std::collections::{VecDeque, HashSet};
+use day6::bitmask::find_marker_fast;
+use day6::decoder::Decoder;
+use day6::stream::{scan, MarkerKind, StreamScanner};
+use std::io::{self, Read};
 use std::time::Instant;
 
-fn check_duplicates(v: &VecDeque<char>) -> bool {
-    let mut set = HashSet::new();
-    for c in v {
-        if set.contains(c) {
-            return true;
-        }
-        set.insert(c);
-    }
-    return false
-}
+mod bench;
 
-fn find_seq_start(s: &str, contig_count: usize) -> usize {
-    let mut buf: VecDeque<char> = VecDeque::new();
-
-    for c in s.chars().enumerate() {
-        // init
-        if buf.len() < contig_count {
-            buf.push_back(c.1);
-        } else {
-            if !check_duplicates(&buf) {
-                return c.0;
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    match args.get(1).map(|x| x.as_str()) {
+        Some("bench") => {
+            // cargo run --release -- bench [characters]
+            bench::bench(
+                args.get(2)
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(10_000_000),
+            );
+            return;
+        }
+        Some("decode") => {
+            // cat log | cargo run -- decode [--message]
+            let kind = if args.iter().any(|x| x == "--message") {
+                MarkerKind::StartOfMessage
+            } else {
+                MarkerKind::StartOfPacket
+            };
+            let mut stream = vec![];
+            if let Err(error) = io::stdin().lock().read_to_end(&mut stream) {
+                eprintln!("{error}");
+                return;
+            }
+            let decoder = Decoder::new(&stream, kind);
+            println!("preamble: {} bytes", decoder.preamble().len());
+            for frame in decoder {
+                println!(
+                    "marker {} payload {} length {}: {}",
+                    frame.marker,
+                    frame.offset,
+                    frame.len(),
+                    String::from_utf8_lossy(frame.payload)
+                );
+            }
+            return;
+        }
+        Some("stream") => {
+            // cat log | cargo run -- stream [--every]
+            let mut scanner = if args.iter().any(|x| x == "--every") {
+                StreamScanner::every()
+            } else {
+                StreamScanner::first()
+            };
+            let result = scan(io::stdin().lock(), &mut scanner, |marker| {
+                println!("{:?} {}", marker.kind, marker.position)
+            });
+            if let Err(error) = result {
+                eprintln!("{error}");
             }
-            buf.pop_front();
-            buf.push_back(c.1);
+            return;
         }
+        _ => (),
     }
-    0
-}
 
-fn main() {
     let input = include_str!("input.txt").to_string();
     let input = input.trim();
 
+    let pretty = |marker: Option<usize>| marker.map_or("none".to_string(), |x| x.to_string());
+
     let start = Instant::now();
     // answer 1: 1109 109µs
-    println!("answer 1: {} {:?}", find_seq_start(&input, 4), start.elapsed());
+    println!(
+        "answer 1: {} {:?}",
+        pretty(find_marker_fast(input, 4)),
+        start.elapsed()
+    );
     let start = Instant::now();
     // answer 2: 3965 711.875µs
-    println!("answer 2: {} {:?}", find_seq_start(&input, 14), start.elapsed());
+    println!(
+        "answer 2: {} {:?}",
+        pretty(find_marker_fast(input, 14)),
+        start.elapsed()
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day6::marker::find_marker;
     use pretty_assertions::assert_eq;
 
     #[test]
-    fn test_find_seq_start() {
-        // size - 4
-        assert_eq!(find_seq_start("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), 5);
-        assert_eq!(find_seq_start("nppdvjthqldpwncqszvftbrmjlhg", 4), 6);
-        assert_eq!(find_seq_start("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4), 10);
-        assert_eq!(find_seq_start("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4), 11);
-
-        // size - 14
-        assert_eq!(find_seq_start("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), 19);
-        assert_eq!(find_seq_start("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), 23);
-        assert_eq!(find_seq_start("nppdvjthqldpwncqszvftbrmjlhg", 14), 23);
-        assert_eq!(find_seq_start("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14), 29);
-        assert_eq!(find_seq_start("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), 26);
-    }
-
-    #[test]
-    fn test_check_duplicates() {
-        let v1 = VecDeque::from(['a', 'b', 'c', 'd']);
-        assert_eq!(check_duplicates(&v1), false);
-
-        let v2 = VecDeque::from(['a', 'b', 'c', 'c']);
-        assert_eq!(check_duplicates(&v2), true);
-
-        let v3 = VecDeque::from(['a', 'a', 'c', 'c']);
-        assert_eq!(check_duplicates(&v3), true);
+    fn test_all() {
+        let input = include_str!("input.txt").trim();
+        assert_eq!(find_marker(input, 4), Some(1109));
+        assert_eq!(find_marker(input, 14), Some(3965));
+        assert_eq!(find_marker_fast(input, 4), Some(1109));
+        assert_eq!(find_marker_fast(input, 14), Some(3965));
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Sliding window over a stream of symbols, keeping a count of every symbol in the
+/// window so each step is O(1).
+#[derive(Debug, Clone)]
+pub struct MarkerDetector<T> {
+    window: usize,
+    buf: VecDeque<T>,
+    counts: HashMap<T, usize>,
+    /// Number of symbols that are in the window more than once.
+    duplicates: usize,
+    /// Number of symbols pushed so far.
+    position: usize,
+}
+
+impl<T: Copy + Eq + Hash> MarkerDetector<T> {
+    pub fn new(window: usize) -> Self {
+        MarkerDetector {
+            window,
+            buf: VecDeque::with_capacity(window + 1),
+            counts: HashMap::new(),
+            duplicates: 0,
+            position: 0,
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Adds the next symbol, returns true if the last `window` symbols are all
+    /// different.
+    pub fn push(&mut self, symbol: T) -> bool {
+        if self.window == 0 {
+            self.position += 1;
+            return true;
+        }
+
+        self.buf.push_back(symbol);
+        let count = self.counts.entry(symbol).or_insert(0);
+        *count += 1;
+        if *count == 2 {
+            self.duplicates += 1;
+        }
+
+        if self.buf.len() > self.window {
+            let removed = self.buf.pop_front().unwrap();
+            let count = self.counts.get_mut(&removed).unwrap();
+            *count -= 1;
+            if *count == 1 {
+                self.duplicates -= 1;
+            }
+        }
+
+        self.position += 1;
+        self.buf.len() == self.window && self.duplicates == 0
+    }
+
+    /// Forgets the window, e.g. to look for the next marker after one was found.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.counts.clear();
+        self.duplicates = 0;
+    }
+}
+
+/// Number of characters processed until the first `window` different characters in a
+/// row end, or `None` if there is no such marker.
+pub fn find_marker(s: &str, window: usize) -> Option<usize> {
+    if window == 0 {
+        return Some(0);
+    }
+    let mut detector = MarkerDetector::new(window);
+    s.chars()
+        .any(|c| detector.push(c))
+        .then(|| detector.position())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_find_marker() {
+        // size - 4
+        assert_eq!(find_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), Some(5));
+        assert_eq!(find_marker("nppdvjthqldpwncqszvftbrmjlhg", 4), Some(6));
+        assert_eq!(
+            find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4),
+            Some(10)
+        );
+        assert_eq!(find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4), Some(11));
+
+        // size - 14
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(find_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), Some(23));
+        assert_eq!(find_marker("nppdvjthqldpwncqszvftbrmjlhg", 14), Some(23));
+        assert_eq!(
+            find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14),
+            Some(29)
+        );
+        assert_eq!(
+            find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14),
+            Some(26)
+        );
+    }
+
+    #[test]
+    fn test_find_marker_edge_cases() {
+        assert_eq!(find_marker("aaaaaaa", 2), None);
+        assert_eq!(find_marker("abc", 4), None);
+        assert_eq!(find_marker("", 1), None);
+        assert_eq!(find_marker("abcd", 4), Some(4));
+        assert_eq!(find_marker("aab", 1), Some(1));
+        assert_eq!(find_marker("abc", 0), Some(0));
+        // works for any characters, not only ASCII
+        assert_eq!(find_marker("ééàèé", 3), Some(4));
+    }
+
+    #[test]
+    fn test_marker_detector() {
+        let mut detector = MarkerDetector::new(3);
+        let found = "abcabbcd".chars().filter(|c| detector.push(*c)).count();
+        // abc, bca, cab, bcd
+        assert_eq!(found, 4);
+        assert_eq!(detector.position(), 8);
+
+        detector.reset();
+        assert_eq!(detector.push('x'), false);
+        assert_eq!(detector.push('y'), false);
+        assert_eq!(detector.push('z'), true);
+        assert_eq!(detector.position(), 11);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::marker::MarkerDetector;
+use std::io::{self, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    StartOfPacket,
+    StartOfMessage,
+}
+
+impl MarkerKind {
+    /// Number of different characters in a row making up the marker.
+    pub fn window(&self) -> usize {
+        match self {
+            MarkerKind::StartOfPacket => 4,
+            MarkerKind::StartOfMessage => 14,
+        }
+    }
+}
+
+/// A marker ending right before byte `position` of the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    pub position: usize,
+}
+
+/// Finds markers in a stream fed in chunks of any size, using constant memory. It
+/// doesn't do any I/O itself, so it can be driven from a [`Read`] with [`scan`] or
+/// from an async byte stream by calling [`StreamScanner::feed`] with every chunk.
+#[derive(Debug, Clone)]
+pub struct StreamScanner {
+    detectors: [(MarkerKind, MarkerDetector<u8>, bool); 2],
+    every: bool,
+}
+
+impl StreamScanner {
+    /// Reports only the first marker of each kind.
+    pub fn first() -> Self {
+        StreamScanner::new(false)
+    }
+
+    /// Reports every marker. After a marker is found, the next one of the same kind
+    /// has to start after it.
+    pub fn every() -> Self {
+        StreamScanner::new(true)
+    }
+
+    fn new(every: bool) -> Self {
+        let detector = |kind: MarkerKind| (kind, MarkerDetector::new(kind.window()), false);
+        StreamScanner {
+            detectors: [
+                detector(MarkerKind::StartOfPacket),
+                detector(MarkerKind::StartOfMessage),
+            ],
+            every,
+        }
+    }
+
+    /// True once nothing more can be reported.
+    pub fn is_done(&self) -> bool {
+        !self.every && self.detectors.iter().all(|(_, _, found)| *found)
+    }
+
+    /// Processes the next chunk of the stream, calling `found` for every marker in it,
+    /// in stream order.
+    pub fn feed<F: FnMut(Marker)>(&mut self, chunk: &[u8], mut found: F) {
+        for byte in chunk {
+            for (kind, detector, seen) in self.detectors.iter_mut() {
+                if *seen && !self.every {
+                    continue;
+                }
+                if detector.push(*byte) {
+                    *seen = true;
+                    found(Marker {
+                        kind: *kind,
+                        position: detector.position(),
+                    });
+                    detector.reset();
+                }
+            }
+        }
+    }
+}
+
+/// Reads the whole stream chunk by chunk, calling `found` for every marker. Stops
+/// reading early if `scanner` has nothing more to report.
+pub fn scan<R: Read, F: FnMut(Marker)>(
+    mut reader: R,
+    scanner: &mut StreamScanner,
+    mut found: F,
+) -> io::Result<()> {
+    let mut buf = [0; 64 * 1024];
+    while !scanner.is_done() {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        scanner.feed(&buf[..n], &mut found);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn collect(reader: impl Read, mut scanner: StreamScanner) -> Vec<Marker> {
+        let mut markers = vec![];
+        scan(reader, &mut scanner, |m| markers.push(m)).unwrap();
+        markers
+    }
+
+    fn marker(kind: MarkerKind, position: usize) -> Marker {
+        Marker { kind, position }
+    }
+
+    #[test]
+    fn test_scan_first() {
+        let stream = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        assert_eq!(
+            collect(stream.as_bytes(), StreamScanner::first()),
+            vec![
+                marker(MarkerKind::StartOfPacket, 7),
+                marker(MarkerKind::StartOfMessage, 19)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_every() {
+        let markers = collect("abcdabcdaaaaaaxyzw".as_bytes(), StreamScanner::every());
+        assert_eq!(
+            markers,
+            vec![
+                marker(MarkerKind::StartOfPacket, 4),
+                marker(MarkerKind::StartOfPacket, 8),
+                marker(MarkerKind::StartOfPacket, 17)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_feed_chunks() {
+        // markers spanning chunk boundaries are found no matter how the stream is split
+        let stream = include_str!("input.txt").trim().as_bytes();
+        for chunk_size in [1, 3, 7, 4096] {
+            let mut scanner = StreamScanner::first();
+            let mut markers = vec![];
+            for chunk in stream.chunks(chunk_size) {
+                scanner.feed(chunk, |m| markers.push(m));
+            }
+            assert!(scanner.is_done());
+            assert_eq!(
+                markers,
+                vec![
+                    marker(MarkerKind::StartOfPacket, 1109),
+                    marker(MarkerKind::StartOfMessage, 3965)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_scan_stops_early() {
+        // a reader that fails after the markers, it must not be read that far
+        struct Failing<'a>(&'a [u8]);
+        impl Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(io::Error::other("read too far"));
+                }
+                let n = self.0.read(buf)?;
+                Ok(n)
+            }
+        }
+        let markers = collect(Failing(b"abcdefghijklmn"), StreamScanner::first());
+        assert_eq!(markers.len(), 2);
+        assert!(scan(Failing(b"abcd"), &mut StreamScanner::first(), |_| ()).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::fs::{Filesystem, NodeId, NodeKind};
+use crate::transcript::write_transcript;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Names that would not stay inside their parent once joined to its path.
+fn check_name(name: &str) -> io::Result<&str> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid name {name:?}"),
+        ))
+    } else {
+        Ok(name)
+    }
+}
+
+/// Recreates the filesystem below `root`, which must not exist yet. Files have their
+/// recorded size but no content, so they are sparse where the OS supports it.
+pub fn export_dir(filesystem: &Filesystem, root: &Path) -> io::Result<()> {
+    fn visit(filesystem: &Filesystem, id: NodeId, path: &Path) -> io::Result<()> {
+        fs::create_dir(path)?;
+        for child in filesystem.node(id).children() {
+            let node = filesystem.node(child);
+            let path = path.join(check_name(&node.name)?);
+            match node.kind {
+                NodeKind::Dir => visit(filesystem, child, &path)?,
+                NodeKind::File => File::create(&path)?.set_len(node.size as u64)?,
+            }
+        }
+        Ok(())
+    }
+
+    visit(filesystem, Filesystem::ROOT, root)
+}
+
+const BLOCK: usize = 512;
+
+/// Writes the filesystem as a ustar archive. Files are filled with zeros, so the
+/// archive is as large as the filesystem.
+pub fn export_tar(filesystem: &Filesystem, mut out: impl Write) -> io::Result<()> {
+    let zeros = [0; BLOCK];
+    // everything but the root, with paths relative to it
+    let mut stack = filesystem
+        .node(Filesystem::ROOT)
+        .children()
+        .rev()
+        .map(|x| Ok((x, check_name(&filesystem.node(x).name)?.to_string())))
+        .collect::<io::Result<Vec<_>>>()?;
+    while let Some((id, path)) = stack.pop() {
+        let node = filesystem.node(id);
+        match node.kind {
+            NodeKind::Dir => {
+                out.write_all(&tar_header(&format!("{path}/"), node.kind, 0)?)?;
+                for child in node.children().rev() {
+                    let name = check_name(&filesystem.node(child).name)?;
+                    stack.push((child, format!("{path}/{name}")));
+                }
+            }
+            NodeKind::File => {
+                out.write_all(&tar_header(&path, node.kind, node.size)?)?;
+                let mut remaining = node.size.div_ceil(BLOCK) * BLOCK;
+                while remaining > 0 {
+                    out.write_all(&zeros)?;
+                    remaining -= BLOCK;
+                }
+            }
+        }
+    }
+    // end of archive
+    out.write_all(&zeros)?;
+    out.write_all(&zeros)?;
+    out.flush()
+}
+
+fn tar_header(path: &str, kind: NodeKind, size: usize) -> io::Result<[u8; BLOCK]> {
+    let too_long = || io::Error::new(io::ErrorKind::InvalidInput, format!("{path} is too long"));
+    // names over 100 bytes are split into a prefix and a name on a `/`
+    let (prefix, name) = if path.len() <= 100 {
+        ("", path)
+    } else {
+        let split = path
+            .match_indices('/')
+            .map(|(i, _)| i)
+            .rfind(|i| *i <= 155 && i + 1 < path.len() && path.len() - i - 1 <= 100)
+            .ok_or_else(too_long)?;
+        (&path[..split], &path[split + 1..])
+    };
+    // 11 octal digits, up to 64 GiB
+    if size >= 1 << 33 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{path} is too large"),
+        ));
+    }
+
+    let mut header = [0; BLOCK];
+    let mut field =
+        |offset: usize, value: &[u8]| header[offset..offset + value.len()].copy_from_slice(value);
+    field(0, name.as_bytes());
+    let mode = match kind {
+        NodeKind::Dir => "0000755",
+        NodeKind::File => "0000644",
+    };
+    field(100, mode.as_bytes());
+    field(108, b"0000000");
+    field(116, b"0000000");
+    field(124, format!("{size:011o}").as_bytes());
+    field(136, b"00000000000");
+    let typeflag = match kind {
+        NodeKind::Dir => b'5',
+        NodeKind::File => b'0',
+    };
+    field(156, &[typeflag]);
+    field(257, b"ustar\x0000");
+    field(345, prefix.as_bytes());
+
+    // computed with the checksum itself as spaces
+    field(148, b"        ");
+    let checksum = header.iter().map(|x| *x as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    Ok(header)
+}
+
+/// Reads a real directory into a filesystem, using the length of every file. Symbolic
+/// links are an error, since the transcript has no way to show them.
+pub fn read_dir(root: &Path) -> io::Result<Filesystem> {
+    fn visit(filesystem: &mut Filesystem, id: NodeId, path: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|name| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{name:?} is not UTF-8"))
+            })?;
+            let metadata = entry.metadata()?;
+            if metadata.is_symlink() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is a symbolic link", entry.path().display()),
+                ));
+            }
+            if metadata.is_dir() {
+                let child = filesystem.add_dir(id, &name);
+                visit(filesystem, child, &entry.path())?;
+            } else {
+                filesystem.add_file(id, &name, metadata.len() as usize);
+            }
+        }
+        Ok(())
+    }
+
+    let mut filesystem = Filesystem::new();
+    visit(&mut filesystem, Filesystem::ROOT, root)?;
+    Ok(filesystem)
+}
+
+/// A transcript exploring a real directory, as if it was the device's root.
+pub fn import_dir(root: &Path) -> io::Result<String> {
+    Ok(write_transcript(&read_dir(root)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::parse_transcript;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("day7-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let transcript = write_transcript(&parse_transcript(include_str!("test.txt")).unwrap());
+        let root = temp_path("round-trip");
+        export_dir(&parse_transcript(&transcript).unwrap(), &root).unwrap();
+        let imported = import_dir(&root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(imported.unwrap(), transcript);
+    }
+
+    #[test]
+    fn test_export_dir_exists() {
+        let root = temp_path("exists");
+        fs::create_dir(&root).unwrap();
+        let result = export_dir(&Filesystem::new(), &root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn test_export_dir_invalid_name() {
+        let root = temp_path("invalid-name");
+        for name in ["..", ".", "", "/etc", "a/b"] {
+            let mut filesystem = Filesystem::new();
+            filesystem.add_file(Filesystem::ROOT, name, 1);
+            let result = export_dir(&filesystem, &root);
+            fs::remove_dir_all(&root).unwrap();
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+            assert!(export_tar(&filesystem, io::sink()).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_dir_symlink() {
+        let root = temp_path("symlink");
+        fs::create_dir_all(root.join("a")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("b")).unwrap();
+        let result = read_dir(&root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_export_tar() {
+        let filesystem = parse_transcript("$ ls\ndir a\n600 b\n$ cd a\n$ ls\n0 c\n").unwrap();
+        let mut tar = vec![];
+        export_tar(&filesystem, &mut tar).unwrap();
+        // a/, a/c, b and its 2 blocks of content, and the end of the archive
+        assert_eq!(tar.len(), 7 * BLOCK);
+        assert_eq!(&tar[..3], b"a/\0");
+        assert_eq!(&tar[BLOCK..BLOCK + 4], b"a/c\0");
+        assert_eq!(&tar[2 * BLOCK..2 * BLOCK + 2], b"b\0");
+        assert_eq!(&tar[2 * BLOCK + 124..2 * BLOCK + 135], b"00000001130");
+        assert_eq!(&tar[257..263], b"ustar\0");
+    }
+
+    #[test]
+    fn test_tar_header_long_path() {
+        let dir = "d".repeat(120);
+        let header = tar_header(&format!("{dir}/file"), NodeKind::File, 1).unwrap();
+        assert_eq!(&header[..5], b"file\0");
+        assert_eq!(&header[345..465], dir.as_bytes());
+        assert!(tar_header(&"f".repeat(101), NodeKind::File, 1).is_err());
+    }
+
+    #[test]
+    fn test_tar_header_large_file() {
+        let header = tar_header("file", NodeKind::File, (1 << 33) - 1).unwrap();
+        assert_eq!(&header[124..136], b"77777777777\0");
+        let error = tar_header("file", NodeKind::File, 1 << 33).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+/// Index of a node in its [`Filesystem`].
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    File,
+    Dir,
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+    /// Size of the file itself, 0 for directories.
+    pub size: usize,
+    /// Size of the node and everything below it, kept up to date on insertion.
+    total_size: usize,
+    parent: Option<NodeId>,
+    children: BTreeMap<String, NodeId>,
+}
+
+impl Node {
+    fn new(name: &str, kind: NodeKind, size: usize, parent: Option<NodeId>) -> Self {
+        Node {
+            name: name.to_string(),
+            kind,
+            size,
+            total_size: size,
+            parent,
+            children: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind == NodeKind::Dir
+    }
+
+    pub fn total_size(&self) -> usize {
+        self.total_size
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// Children by name.
+    pub fn children(&self) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        self.children.values().copied()
+    }
+}
+
+/// Tree of nodes stored in a single `Vec`, linked by index. Nodes are never removed.
+#[derive(Debug, Clone)]
+pub struct Filesystem {
+    nodes: Vec<Node>,
+}
+
+impl Default for Filesystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filesystem {
+    pub const ROOT: NodeId = 0;
+
+    /// An empty filesystem with only the root directory `/`.
+    pub fn new() -> Self {
+        Filesystem {
+            nodes: vec![Node::new("/", NodeKind::Dir, 0, None)],
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn child(&self, parent: NodeId, name: &str) -> Option<NodeId> {
+        self.nodes[parent].children.get(name).copied()
+    }
+
+    /// Absolute path of a node, `/` for the root and `/a/e` below it.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(self.nodes[current].name.as_str());
+            current = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// The node at an absolute path like `/a/e`. A trailing `/` is allowed.
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.strip_prefix('/')?
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .try_fold(Self::ROOT, |id, name| self.child(id, name))
+    }
+
+    /// Adds a directory, or returns the existing one with that name. Panics if there is
+    /// a file with that name.
+    pub fn add_dir(&mut self, parent: NodeId, name: &str) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            assert!(self.nodes[id].is_dir(), "{name} is a file");
+            return id;
+        }
+        self.push(parent, Node::new(name, NodeKind::Dir, 0, Some(parent)))
+    }
+
+    /// Adds a file, replacing the size of an existing one with that name. Panics if
+    /// there is a directory with that name.
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        let id = match self.child(parent, name) {
+            Some(id) => {
+                assert!(!self.nodes[id].is_dir(), "{name} is a directory");
+                let old = self.nodes[id].size;
+                self.nodes[id].size = size;
+                self.nodes[id].total_size = size;
+                self.update_ancestors(parent, |total| total - old);
+                id
+            }
+            None => self.push(parent, Node::new(name, NodeKind::File, size, Some(parent))),
+        };
+        self.update_ancestors(parent, |total| total + size);
+        id
+    }
+
+    fn push(&mut self, parent: NodeId, node: Node) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes[parent].children.insert(node.name.clone(), id);
+        self.nodes.push(node);
+        id
+    }
+
+    fn update_ancestors(&mut self, from: NodeId, f: impl Fn(usize) -> usize) {
+        let mut current = Some(from);
+        while let Some(id) = current {
+            self.nodes[id].total_size = f(self.nodes[id].total_size);
+            current = self.nodes[id].parent;
+        }
+    }
+
+    /// Every node depth first, parents before their children and children by name.
+    pub fn walk(&self) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = vec![Self::ROOT];
+        std::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.nodes[id].children().rev());
+            Some(id)
+        })
+    }
+
+    /// The root and every directory below it, in [`walk`](Self::walk) order.
+    pub fn dirs(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk().filter(|x| self.nodes[*x].is_dir())
+    }
+
+    /// Directories directly in `id`, by name.
+    pub fn dirs_in(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes[id]
+            .children()
+            .filter(|x| self.nodes[*x].is_dir())
+    }
+
+    /// Every file, in [`walk`](Self::walk) order.
+    pub fn files(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk().filter(|x| !self.nodes[*x].is_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_total_size() {
+        let mut fs = Filesystem::new();
+        let a = fs.add_dir(Filesystem::ROOT, "a");
+        let e = fs.add_dir(a, "e");
+        fs.add_file(e, "i", 584);
+        fs.add_file(a, "f", 29116);
+        fs.add_file(Filesystem::ROOT, "b.txt", 14848514);
+        assert_eq!(fs.node(e).total_size(), 584);
+        assert_eq!(fs.node(a).total_size(), 29700);
+        assert_eq!(fs.node(Filesystem::ROOT).total_size(), 14878214);
+
+        // listing the same file again does not count it twice
+        fs.add_file(e, "i", 600);
+        assert_eq!(fs.add_dir(Filesystem::ROOT, "a"), a);
+        assert_eq!(fs.node(a).total_size(), 29716);
+        assert_eq!(fs.node(a).children().count(), 2);
+
+        // empty files and directories are still what they are
+        let empty = fs.add_dir(a, "empty");
+        let zero = fs.add_file(a, "zero", 0);
+        assert!(fs.node(empty).is_dir());
+        assert!(!fs.node(zero).is_dir());
+    }
+
+    #[test]
+    fn test_path_and_lookup() {
+        let mut fs = Filesystem::new();
+        let a = fs.add_dir(Filesystem::ROOT, "a");
+        let e = fs.add_dir(a, "e");
+        let i = fs.add_file(e, "i", 584);
+        assert_eq!(fs.path(Filesystem::ROOT), "/");
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(fs.path(i), "/a/e/i");
+
+        assert_eq!(fs.lookup("/"), Some(Filesystem::ROOT));
+        assert_eq!(fs.lookup("/a/e"), Some(e));
+        assert_eq!(fs.lookup("/a/e/"), Some(e));
+        assert_eq!(fs.lookup("/a/e/i"), Some(i));
+        assert_eq!(fs.lookup("/a/x"), None);
+        assert_eq!(fs.lookup("/a/e/i/j"), None);
+        assert_eq!(fs.lookup("a/e"), None);
+    }
+
+    #[test]
+    fn test_dirs() {
+        let mut fs = Filesystem::new();
+        let b = fs.add_dir(Filesystem::ROOT, "b");
+        let a = fs.add_dir(Filesystem::ROOT, "a");
+        let c = fs.add_dir(a, "c");
+        fs.add_file(b, "x", 1);
+        fs.add_file(c, "y", 2);
+        fs.add_file(Filesystem::ROOT, "z", 3);
+        fs.add_dir(b, "empty");
+        let names = fs
+            .dirs()
+            .map(|x| fs.node(x).name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["/", "a", "c", "b", "empty"]);
+    }
+}
@@ -0,0 +1,54 @@
+use fs::Filesystem;
+use planner::{smallest_dir, Disk};
+
+pub mod export;
+pub mod fs;
+pub mod planner;
+pub mod report;
+pub mod transcript;
+
+pub fn part1(filesystem: &Filesystem) -> usize {
+    filesystem
+        .dirs()
+        .map(|id| filesystem.node(id).total_size())
+        .filter(|size| size < &100_000)
+        .sum()
+}
+
+/// Path and size of the directory to delete.
+pub fn part2(filesystem: &Filesystem) -> (String, usize) {
+    let plan = smallest_dir(filesystem, &Disk::DEVICE).unwrap();
+    (filesystem.path(plan.nodes[0]), plan.freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::parse_transcript;
+    use pretty_assertions::assert_eq;
+
+    const TEST_INPUT: &str = include_str!("test.txt");
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&parse_transcript(TEST_INPUT).unwrap()), 95437);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(
+            part2(&parse_transcript(TEST_INPUT).unwrap()),
+            ("/d".to_string(), 24933642)
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        let filesystem = parse_transcript(include_str!("input.txt")).unwrap();
+        assert_eq!(part1(&filesystem), 2031851);
+        assert_eq!(
+            part2(&filesystem),
+            ("/hqdssf/sfccfsrd/zmrt".to_string(), 2568781)
+        );
+    }
+}
@@ -1,152 +1,138 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::fmt;
-use std::rc::Rc;
+use day7::export::{export_dir, export_tar, import_dir};
+use day7::fs::Filesystem;
+use day7::planner::{alternatives, minimal_deletion, Disk, Plan};
+use day7::report::{du, largest_files, tree};
+use day7::transcript::parse_transcript;
+use day7::{part1, part2};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 use std::time::Instant;
 
-lazy_static! {
-    static ref RE_LS: Regex = Regex::new(r"^\$ ls$").unwrap();
-    static ref RE_LS_DIR: Regex = Regex::new(r"^dir (?P<name>[a-z]+)$").unwrap();
-    static ref RE_LS_FILE: Regex = Regex::new(r"^(?P<size>[0-9]+) (?P<name>.+)$").unwrap();
-    static ref RE_CD_X: Regex = Regex::new(r"^\$ cd (?P<name>[a-z]+)$").unwrap();
-    static ref RE_CD_UP: Regex = Regex::new(r"^\$ cd ..$").unwrap();
-    static ref RE_CD_ROOT: Regex = Regex::new(r"^\$ cd /$").unwrap();
-}
-
-// https://fasterthanli.me/series/advent-of-code-2022/part-7
-type NodeHandle = Rc<RefCell<Node>>;
-
-struct Node {
-    name: String,
-    size: usize,
-    children: HashMap<String, NodeHandle>,
-    parent: Option<NodeHandle>,
-}
-
-impl fmt::Debug for Node {
-    // make sure we don't print the parent
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Node")
-            .field("name", &self.name)
-            .field("size", &self.size)
-            .field("children", &self.children)
-            .finish()
+fn print_plan(filesystem: &Filesystem, plan: &Plan) {
+    for &id in &plan.nodes {
+        println!(
+            "{:>10} {}",
+            filesystem.node(id).total_size(),
+            filesystem.path(id)
+        );
     }
 }
 
-impl Node {
-    fn new(name: &str, size: usize, parent: Option<NodeHandle>) -> Self {
-        Node {
-            name: name.to_string(),
-            size: size,
-            children: HashMap::new(),
-            parent: parent,
-        }
-    }
-
-    fn is_dir(&self) -> bool {
-        self.size == 0 && !self.children.is_empty()
-    }
-
-    fn get_total_size(&self) -> usize {
-        // is a file
-        if self.size != 0 {
-            return self.size;
+fn main() {
+    let filesystem = match parse_transcript(include_str!("input.txt")) {
+        Ok(filesystem) => filesystem,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
         }
-        // is an empty dir
-        if self.children.len() == 0 {
-            return 0;
-        } else {
-            // a dir with files
-            return self
-                .children
-                .iter()
-                .fold(0, |acc, (_, node)| node.borrow_mut().get_total_size() + acc);
+    };
+
+    let args = std::env::args().collect::<Vec<_>>();
+    match args.get(1).map(|x| x.as_str()) {
+        Some("tree") => {
+            // cargo run -- tree [path]
+            let path = args.get(2).map_or("/", |x| x.as_str());
+            match filesystem.lookup(path) {
+                Some(id) => print!("{}", tree(&filesystem, id)),
+                None => eprintln!("{path}: no such file or directory"),
+            }
+            return;
         }
-    }
-}
-
-fn all_dirs(n: NodeHandle) -> Box<dyn Iterator<Item = NodeHandle>> {
-    let children: Vec<_> = n.borrow().children.values().cloned().collect();
-    let it = std::iter::once(n).chain(
-        children
-            .into_iter()
-            .filter(|child| Node::is_dir(&child.borrow()))
-            .map(|child| all_dirs(child))
-            .flatten(),
-    );
-    Box::new(it)
-}
-
-fn main() {
-    let input = include_str!("input.txt");
-
-    let filesystem = Rc::new(RefCell::new(Node::new("/", 0, None)));
-    let mut current = filesystem.clone();
-
-    // parse input
-    for line in input.lines() {
-        if RE_LS.is_match(line) {
-            // do nothing
+        Some("du") => {
+            // cargo run -- du [glob]
+            for entry in du(&filesystem, args.get(2).map(|x| x.as_str())) {
+                println!("{:>10} {}", entry.size, entry.path);
+            }
+            return;
         }
-        if let Some(cap) = RE_LS_DIR.captures(line) {
-            let name = cap.name("name").unwrap().as_str();
-
-            let new_dir = Node::new(name, 0, Some(current.clone()));
-            current
-                .borrow_mut()
-                .children
-                .insert(name.to_owned(), Rc::new(RefCell::new(new_dir)));
+        Some("largest") => {
+            // cargo run -- largest [count] [glob]
+            let n = args.get(2).and_then(|x| x.parse().ok()).unwrap_or(10);
+            for entry in largest_files(&filesystem, n, args.get(3).map(|x| x.as_str())) {
+                println!("{:>10} {}", entry.size, entry.path);
+            }
+            return;
         }
-        if let Some(cap) = RE_LS_FILE.captures(line) {
-            let name = cap.name("name").unwrap().as_str();
-            let size = cap.name("size").unwrap().as_str();
-
-            let new_file = Node::new(name, size.parse().unwrap(), Some(current.clone()));
-            current
-                .borrow_mut()
-                .children
-                .insert(name.to_owned(), Rc::new(RefCell::new(new_file)));
+        Some("plan") => {
+            // cargo run -- plan [capacity] [required]
+            let disk = Disk {
+                capacity: args
+                    .get(2)
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(Disk::DEVICE.capacity),
+                required: args
+                    .get(3)
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(Disk::DEVICE.required),
+            };
+            println!(
+                "used {}, need to free {}",
+                disk.used(&filesystem),
+                disk.to_free(&filesystem)
+            );
+            match minimal_deletion(&filesystem, &disk) {
+                Ok(plan) => {
+                    println!(
+                        "minimal deletion, {} in {} nodes:",
+                        plan.freed,
+                        plan.nodes.len()
+                    );
+                    print_plan(&filesystem, &plan);
+                }
+                Err(error) => println!("{error}"),
+            }
+            let alternatives = alternatives(&filesystem, &disk);
+            if !alternatives.is_empty() {
+                println!("single directories:");
+            }
+            for plan in alternatives {
+                print_plan(&filesystem, &plan);
+            }
+            return;
         }
-        if let Some(cap) = RE_CD_X.captures(line) {
-            let name = cap.name("name").unwrap().as_str();
-
-            let new_current = current.borrow_mut().children.get(name).unwrap().clone();
-            current = new_current;
+        Some("export") => {
+            // cargo run -- export <directory>
+            let Some(root) = args.get(2) else {
+                eprintln!("missing directory");
+                return;
+            };
+            if let Err(error) = export_dir(&filesystem, Path::new(root)) {
+                eprintln!("{error}");
+            }
+            return;
         }
-        if RE_CD_UP.is_match(line) {
-            let new_current = current.borrow_mut().parent.clone().unwrap();
-            current = new_current;
+        Some("tar") => {
+            // cargo run -- tar <archive>
+            let Some(archive) = args.get(2) else {
+                eprintln!("missing archive");
+                return;
+            };
+            let result = File::create(archive)
+                .and_then(|file| export_tar(&filesystem, BufWriter::new(file)));
+            if let Err(error) = result {
+                eprintln!("{error}");
+            }
+            return;
         }
-        if RE_CD_ROOT.is_match(line) {
-            current = filesystem.clone();
+        Some("import") => {
+            // cargo run -- import <directory>
+            match args.get(2).map(|x| import_dir(Path::new(x))) {
+                Some(Ok(transcript)) => print!("{transcript}"),
+                Some(Err(error)) => eprintln!("{error}"),
+                None => eprintln!("missing directory"),
+            }
+            return;
         }
+        _ => (),
     }
 
     // answer 1: 2031851 50.75µs
     let start = Instant::now();
-    println!(
-        "answer 1: {} {:?}",
-        all_dirs(filesystem.clone())
-            .map(|node| node.borrow().get_total_size())
-            .filter(|size| size < &100_000)
-            .sum::<usize>(),
-        start.elapsed()
-    );
+    println!("answer 1: {} {:?}", part1(&filesystem), start.elapsed());
 
     // answer 2: 2568781 48.166µs
     let start = Instant::now();
-    let free_space_now = 70_000_000 - filesystem.borrow_mut().get_total_size();
-    let required_space = 30_000_000 - free_space_now;
-    println!(
-        "answer 2: {} {:?}",
-        all_dirs(filesystem.clone())
-            .map(|node| node.borrow().get_total_size())
-            .filter(|size| size >= &required_space)
-            .min()
-            .unwrap(),
-        start.elapsed()
-    );
+    let (path, size) = part2(&filesystem);
+    println!("answer 2: {size} {path} {:?}", start.elapsed());
 }
@@ -0,0 +1,409 @@
+use crate::fs::{Filesystem, NodeId};
+use std::fmt;
+
+/// Size of the device and the free space an update needs on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disk {
+    pub capacity: usize,
+    pub required: usize,
+}
+
+impl Disk {
+    /// The device from the puzzle.
+    pub const DEVICE: Disk = Disk {
+        capacity: 70_000_000,
+        required: 30_000_000,
+    };
+
+    pub fn used(&self, filesystem: &Filesystem) -> usize {
+        filesystem.node(Filesystem::ROOT).total_size()
+    }
+
+    /// Space to delete before the update fits, 0 if there is already enough.
+    pub fn to_free(&self, filesystem: &Filesystem) -> usize {
+        let free = self.capacity.saturating_sub(self.used(filesystem));
+        self.required.saturating_sub(free)
+    }
+}
+
+/// Nodes to delete, none of them inside another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub nodes: Vec<NodeId>,
+    pub freed: usize,
+}
+
+/// Largest number of sums [`minimal_deletion`] keeps track of, about 64 MiB of memory.
+pub const MAX_SUMS: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanError {
+    /// Even deleting everything does not free enough.
+    NotEnoughSpace,
+    /// More than [`MAX_SUMS`] sums to search through, or more files than can be
+    /// indexed.
+    TooLarge,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::NotEnoughSpace => {
+                write!(f, "not enough space even after deleting everything")
+            }
+            PlanError::TooLarge => write!(f, "too many possible sizes to find the minimum"),
+        }
+    }
+}
+
+/// Directories that free enough space on their own, smallest first.
+pub fn alternatives(filesystem: &Filesystem, disk: &Disk) -> Vec<Plan> {
+    let to_free = disk.to_free(filesystem);
+    let mut plans = filesystem
+        .dirs()
+        .map(|id| Plan {
+            nodes: vec![id],
+            freed: filesystem.node(id).total_size(),
+        })
+        .filter(|x| x.freed >= to_free)
+        .collect::<Vec<_>>();
+    plans.sort_by_key(|x| x.freed);
+    plans
+}
+
+/// The smallest directory that frees enough space on its own, or `None` if even
+/// deleting everything is not enough.
+pub fn smallest_dir(filesystem: &Filesystem, disk: &Disk) -> Option<Plan> {
+    alternatives(filesystem, disk).into_iter().next()
+}
+
+/// Files and directories freeing as little space as possible while still freeing
+/// enough.
+///
+/// Deleting a directory frees as much as deleting all the files in it, so this looks
+/// for the best set of files (a subset sum over a bitset of the sums below the
+/// target), then replaces files by the directories they fill up completely. Sizes are
+/// divided by their greatest common divisor first, so files in whole blocks only need
+/// a sum per block. When more than half of the used space has to go, it looks for the
+/// most that can be kept instead, which needs fewer sums.
+pub fn minimal_deletion(filesystem: &Filesystem, disk: &Disk) -> Result<Plan, PlanError> {
+    let target = disk.to_free(filesystem);
+    if target == 0 {
+        return Ok(Plan {
+            nodes: vec![],
+            freed: 0,
+        });
+    }
+    if disk.used(filesystem) < target {
+        return Err(PlanError::NotEnoughSpace);
+    }
+
+    let files = filesystem
+        .files()
+        .filter(|x| filesystem.node(*x).size > 0)
+        .collect::<Vec<_>>();
+    let sizes = files
+        .iter()
+        .map(|x| filesystem.node(*x).size)
+        .collect::<Vec<_>>();
+    let unit = sizes.iter().fold(0, |a, b| gcd(a, *b));
+    let scaled = sizes.iter().map(|x| x / unit).collect::<Vec<_>>();
+    let target = target.div_ceil(unit);
+    let keep = scaled.iter().sum::<usize>() - target;
+    let deleted = if target <= keep {
+        subset_sum(&scaled, target)?.ok_or(PlanError::NotEnoughSpace)?
+    } else {
+        let mut deleted = vec![true; files.len()];
+        for i in largest_within(&scaled, keep)? {
+            deleted[i] = false;
+        }
+        (0..files.len()).filter(|i| deleted[*i]).collect()
+    };
+    let chosen = deleted.into_iter().map(|i| files[i]).collect::<Vec<_>>();
+    Ok(group_by_dir(filesystem, &chosen))
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Indices of items with the smallest sum that is at least `target`, which must be
+/// more than 0 and at most [`MAX_SUMS`].
+fn subset_sum(sizes: &[usize], target: usize) -> Result<Option<Vec<usize>>, PlanError> {
+    if target > MAX_SUMS || u32::try_from(sizes.len()).is_err() {
+        return Err(PlanError::TooLarge);
+    }
+    // reachable[s] for every sum below the target, first[s] is the item that made it
+    // reachable, so following first[] back to 0 only uses items before it
+    let mut reachable = Bitset::new(target);
+    let mut first = vec![u32::MAX; target];
+    reachable.set(0);
+
+    // (sum before the last item, last item)
+    let mut best: Option<(usize, usize)> = None;
+    for (i, &size) in sizes.iter().enumerate() {
+        // smallest reachable sum that goes over the target with this item
+        if let Some(s) = reachable.first_set_from(target.saturating_sub(size)) {
+            if best.is_none_or(|(b, j)| s + size < b + sizes[j]) {
+                best = Some((s, i));
+            }
+        }
+        if size < target {
+            reachable.shift_or(size, |s| first[s] = i as u32);
+        }
+    }
+
+    let Some((mut s, last)) = best else {
+        return Ok(None);
+    };
+    let mut items = vec![last];
+    while s > 0 {
+        let i = first[s] as usize;
+        items.push(i);
+        s -= sizes[i];
+    }
+    items.reverse();
+    Ok(Some(items))
+}
+
+/// Indices of items with the largest sum that is at most `limit`, which must be below
+/// [`MAX_SUMS`].
+fn largest_within(sizes: &[usize], limit: usize) -> Result<Vec<usize>, PlanError> {
+    if limit >= MAX_SUMS || u32::try_from(sizes.len()).is_err() {
+        return Err(PlanError::TooLarge);
+    }
+    // as in subset_sum, for every sum up to the limit
+    let mut reachable = Bitset::new(limit + 1);
+    let mut first = vec![u32::MAX; limit + 1];
+    reachable.set(0);
+    for (i, &size) in sizes.iter().enumerate() {
+        if size <= limit {
+            reachable.shift_or(size, |s| first[s] = i as u32);
+        }
+    }
+
+    let mut s = reachable.last_set().unwrap_or(0);
+    let mut items = vec![];
+    while s > 0 {
+        let i = first[s] as usize;
+        items.push(i);
+        s -= sizes[i];
+    }
+    items.reverse();
+    Ok(items)
+}
+
+/// Replaces files by the highest directories in which every file is deleted.
+fn group_by_dir(filesystem: &Filesystem, files: &[NodeId]) -> Plan {
+    // size of the deleted files below every node
+    let mut deleted = std::collections::HashMap::new();
+    for &file in files {
+        let size = filesystem.node(file).size;
+        let mut current = Some(file);
+        while let Some(id) = current {
+            *deleted.entry(id).or_insert(0) += size;
+            current = filesystem.node(id).parent();
+        }
+    }
+
+    let mut nodes = vec![];
+    let mut stack = vec![Filesystem::ROOT];
+    while let Some(id) = stack.pop() {
+        let node = filesystem.node(id);
+        match deleted.get(&id) {
+            None => (),
+            Some(&size) if size == node.total_size() => nodes.push(id),
+            Some(_) => stack.extend(node.children().rev()),
+        }
+    }
+    Plan {
+        freed: nodes.iter().map(|x| filesystem.node(*x).total_size()).sum(),
+        nodes,
+    }
+}
+
+/// Fixed size set of `0..len`.
+struct Bitset {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Bitset {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn set(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn first_set_from(&self, from: usize) -> Option<usize> {
+        let mut w = from / 64;
+        let mut word = *self.words.get(w)? & (u64::MAX << (from % 64));
+        loop {
+            if word != 0 {
+                let i = w * 64 + word.trailing_zeros() as usize;
+                return (i < self.len).then_some(i);
+            }
+            w += 1;
+            word = *self.words.get(w)?;
+        }
+    }
+
+    fn last_set(&self) -> Option<usize> {
+        let w = self.words.iter().rposition(|x| *x != 0)?;
+        Some(w * 64 + 63 - self.words[w].leading_zeros() as usize)
+    }
+
+    /// Adds `s + shift` for every `s` in the set, as far as `len`, calling `added` for
+    /// each new element.
+    fn shift_or(&mut self, shift: usize, mut added: impl FnMut(usize)) {
+        let (q, r) = (shift / 64, shift % 64);
+        // from the top, so every word is shifted before it is updated
+        for w in (q..self.words.len()).rev() {
+            let mut shifted = self.words[w - q] << r;
+            if r > 0 && w > q {
+                shifted |= self.words[w - q - 1] >> (64 - r);
+            }
+            let mut new = shifted & !self.words[w];
+            if w == self.words.len() - 1 && !self.len.is_multiple_of(64) {
+                new &= (1 << (self.len % 64)) - 1;
+            }
+            self.words[w] |= new;
+            while new != 0 {
+                added(w * 64 + new.trailing_zeros() as usize);
+                new &= new - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::parse_transcript;
+    use pretty_assertions::assert_eq;
+
+    fn filesystem() -> Filesystem {
+        parse_transcript(include_str!("test.txt")).unwrap()
+    }
+
+    fn names(filesystem: &Filesystem, plan: &Plan) -> Vec<String> {
+        plan.nodes
+            .iter()
+            .map(|x| filesystem.node(*x).name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_to_free() {
+        let filesystem = filesystem();
+        assert_eq!(Disk::DEVICE.to_free(&filesystem), 8381165);
+        let disk = Disk {
+            capacity: 100_000_000,
+            required: 30_000_000,
+        };
+        assert_eq!(disk.to_free(&filesystem), 0);
+    }
+
+    #[test]
+    fn test_smallest_dir() {
+        let filesystem = filesystem();
+        let plan = smallest_dir(&filesystem, &Disk::DEVICE).unwrap();
+        assert_eq!(names(&filesystem, &plan), vec!["d"]);
+        assert_eq!(plan.freed, 24933642);
+        assert_eq!(alternatives(&filesystem, &Disk::DEVICE).len(), 2);
+
+        let disk = Disk {
+            capacity: 70_000_000,
+            required: 80_000_000,
+        };
+        assert_eq!(smallest_dir(&filesystem, &disk), None);
+    }
+
+    #[test]
+    fn test_minimal_deletion() {
+        let filesystem = filesystem();
+        let plan = minimal_deletion(&filesystem, &Disk::DEVICE).unwrap();
+        // a single file is enough, and closer to 8381165 than d
+        assert_eq!(names(&filesystem, &plan), vec!["c.dat"]);
+        assert_eq!(plan.freed, 8504156);
+
+        // everything in a is deleted, so a is deleted instead of its files
+        let disk = Disk {
+            capacity: 48_381_165,
+            required: 94_853 + 8_504_156,
+        };
+        let plan = minimal_deletion(&filesystem, &disk).unwrap();
+        assert_eq!(names(&filesystem, &plan), vec!["a", "c.dat"]);
+        assert_eq!(plan.freed, 8599009);
+
+        let disk = Disk {
+            capacity: 70_000_000,
+            required: 80_000_000,
+        };
+        assert_eq!(
+            minimal_deletion(&filesystem, &disk),
+            Err(PlanError::NotEnoughSpace)
+        );
+    }
+
+    #[test]
+    fn test_minimal_deletion_large() {
+        // whole blocks of 4 KiB only need a sum per block
+        let filesystem =
+            parse_transcript("$ ls\n40960004096 a\n20480012288 b\n24576028672 c\n").unwrap();
+        let disk = Disk {
+            capacity: 90_000_000_000,
+            required: 20_000_000_000,
+        };
+        let plan = minimal_deletion(&filesystem, &disk).unwrap();
+        assert_eq!(names(&filesystem, &plan), vec!["b"]);
+
+        // too many sums up to what has to be freed, but few up to what can be kept
+        let filesystem = parse_transcript("$ ls\n1 a\n50000000 b\n3 c\n").unwrap();
+        let disk = Disk {
+            capacity: 50_000_004,
+            required: 49_000_000,
+        };
+        let plan = minimal_deletion(&filesystem, &disk).unwrap();
+        assert_eq!(names(&filesystem, &plan), vec!["b"]);
+        assert_eq!(plan.freed, 50_000_000);
+
+        // and too many both ways
+        let filesystem = parse_transcript("$ ls\n1 a\n40000000 b\n").unwrap();
+        let disk = Disk {
+            capacity: 40_000_001,
+            required: 20_000_000,
+        };
+        assert_eq!(
+            minimal_deletion(&filesystem, &disk),
+            Err(PlanError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_subset_sum() {
+        assert_eq!(subset_sum(&[5, 3, 4], 7), Ok(Some(vec![1, 2])));
+        assert_eq!(subset_sum(&[100, 3, 4], 70), Ok(Some(vec![0])));
+        assert_eq!(subset_sum(&[64, 65, 1, 200], 130), Ok(Some(vec![0, 1, 2])));
+        assert_eq!(subset_sum(&[1, 2], 4), Ok(None));
+        assert_eq!(subset_sum(&[1, 2], MAX_SUMS + 1), Err(PlanError::TooLarge));
+    }
+
+    #[test]
+    fn test_largest_within() {
+        assert_eq!(largest_within(&[5, 3, 4], 8), Ok(vec![0, 1]));
+        assert_eq!(largest_within(&[5, 3, 4], 7), Ok(vec![1, 2]));
+        assert_eq!(largest_within(&[100, 3, 4], 70), Ok(vec![1, 2]));
+        assert_eq!(largest_within(&[100, 3], 2), Ok(vec![]));
+        assert_eq!(largest_within(&[1, 2], MAX_SUMS), Err(PlanError::TooLarge));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::fs::{Filesystem, NodeId};
+use std::fmt::Write;
+
+/// A node and its total size, for listings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub size: usize,
+}
+
+fn entries(
+    filesystem: &Filesystem,
+    ids: impl Iterator<Item = NodeId>,
+    glob: Option<&str>,
+) -> Vec<Entry> {
+    let mut entries = ids
+        .map(|id| Entry {
+            path: filesystem.path(id),
+            size: filesystem.node(id).total_size(),
+        })
+        .filter(|x| glob.is_none_or(|glob| glob_match(glob, &x.path)))
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries
+}
+
+/// Directories by decreasing total size, like `du -s`, optionally only those whose
+/// path matches `glob`.
+pub fn du(filesystem: &Filesystem, glob: Option<&str>) -> Vec<Entry> {
+    entries(filesystem, filesystem.dirs(), glob)
+}
+
+/// The `n` largest files, optionally only those whose path matches `glob`.
+pub fn largest_files(filesystem: &Filesystem, n: usize, glob: Option<&str>) -> Vec<Entry> {
+    let mut entries = entries(filesystem, filesystem.files(), glob);
+    entries.truncate(n);
+    entries
+}
+
+/// Renders the filesystem below `id` like `tree`, with the total size of every node.
+pub fn tree(filesystem: &Filesystem, id: NodeId) -> String {
+    let mut out = String::new();
+    let node = filesystem.node(id);
+    writeln!(out, "{} ({})", node.name, node.total_size()).unwrap();
+    tree_children(filesystem, id, "", &mut out);
+    out
+}
+
+fn tree_children(filesystem: &Filesystem, id: NodeId, prefix: &str, out: &mut String) {
+    let children = filesystem.node(id).children().collect::<Vec<_>>();
+    for (i, &child) in children.iter().enumerate() {
+        let last = i == children.len() - 1;
+        let node = filesystem.node(child);
+        let branch = if last { "└── " } else { "├── " };
+        writeln!(out, "{prefix}{branch}{} ({})", node.name, node.total_size()).unwrap();
+        let indent = if last { "    " } else { "│   " };
+        tree_children(filesystem, child, &format!("{prefix}{indent}"), out);
+    }
+}
+
+/// Shell-style matching of a whole path: `?` is any character and `*` any characters
+/// except `/`, `**` also matches across directories.
+pub fn glob_match(glob: &str, path: &str) -> bool {
+    fn matches(glob: &[char], path: &[char]) -> bool {
+        match glob {
+            [] => path.is_empty(),
+            ['*', '*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+            ['*', rest @ ..] => {
+                // as far as the end of the current name
+                let end = path.iter().position(|c| *c == '/').unwrap_or(path.len());
+                (0..=end).any(|i| matches(rest, &path[i..]))
+            }
+            ['?', rest @ ..] => {
+                path.first().is_some_and(|c| *c != '/') && matches(rest, &path[1..])
+            }
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+    let glob = glob.chars().collect::<Vec<_>>();
+    let path = path.chars().collect::<Vec<_>>();
+    matches(&glob, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::parse_transcript;
+    use pretty_assertions::assert_eq;
+
+    fn filesystem() -> Filesystem {
+        parse_transcript(include_str!("test.txt")).unwrap()
+    }
+
+    fn paths(entries: &[Entry]) -> Vec<(&str, usize)> {
+        entries.iter().map(|x| (x.path.as_str(), x.size)).collect()
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            tree(&filesystem(), Filesystem::ROOT),
+            "\
+/ (48381165)
+├── a (94853)
+│   ├── e (584)
+│   │   └── i (584)
+│   ├── f (29116)
+│   ├── g (2557)
+│   └── h.lst (62596)
+├── b.txt (14848514)
+├── c.dat (8504156)
+└── d (24933642)
+    ├── d.ext (5626152)
+    ├── d.log (8033020)
+    ├── j (4060174)
+    └── k (7214296)
+"
+        );
+    }
+
+    #[test]
+    fn test_du() {
+        let filesystem = filesystem();
+        assert_eq!(
+            paths(&du(&filesystem, None)),
+            vec![
+                ("/", 48381165),
+                ("/d", 24933642),
+                ("/a", 94853),
+                ("/a/e", 584)
+            ]
+        );
+        assert_eq!(paths(&du(&filesystem, Some("/a*"))), vec![("/a", 94853)]);
+    }
+
+    #[test]
+    fn test_largest_files() {
+        let filesystem = filesystem();
+        assert_eq!(
+            paths(&largest_files(&filesystem, 2, None)),
+            vec![("/b.txt", 14848514), ("/c.dat", 8504156)]
+        );
+        assert_eq!(
+            paths(&largest_files(&filesystem, 10, Some("/a/**"))),
+            vec![
+                ("/a/h.lst", 62596),
+                ("/a/f", 29116),
+                ("/a/g", 2557),
+                ("/a/e/i", 584)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/a/*", "/a/f"));
+        assert!(!glob_match("/a/*", "/a/e/i"));
+        assert!(glob_match("/a/**", "/a/e/i"));
+        assert!(glob_match("**/*.txt", "/b.txt"));
+        assert!(glob_match("/d/d.???", "/d/d.log"));
+        assert!(!glob_match("/d/d.???", "/d/d.logs"));
+        assert!(!glob_match("/?", "//"));
+        assert!(glob_match("/", "/"));
+    }
+}
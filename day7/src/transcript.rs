@@ -0,0 +1,280 @@
+use crate::fs::{Filesystem, NodeId, NodeKind};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+
+lazy_static! {
+    static ref RE_CD: Regex = Regex::new(r"^\$ cd (?P<name>.+)$").unwrap();
+    static ref RE_LS: Regex = Regex::new(r"^\$ ls$").unwrap();
+    static ref RE_DIR: Regex = Regex::new(r"^dir (?P<name>.+)$").unwrap();
+    static ref RE_FILE: Regex = Regex::new(r"^(?P<size>[0-9]+) (?P<name>.+)$").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptErrorKind {
+    UnknownCommand(String),
+    /// `cd ..` in `/`.
+    CdAboveRoot,
+    /// `cd` into a file.
+    NotADirectory(String),
+    /// An `ls` entry whose kind differs from an earlier entry with the same name.
+    KindChanged(String),
+    /// Names cannot be empty, `.`, `..` or contain `/`.
+    InvalidName(String),
+    InvalidEntry(String),
+    /// An entry that is not after an `ls`.
+    UnexpectedOutput(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptError {
+    /// One-based.
+    pub line: usize,
+    pub kind: TranscriptErrorKind,
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            TranscriptErrorKind::UnknownCommand(s) => write!(f, "unknown command {s:?}"),
+            TranscriptErrorKind::CdAboveRoot => write!(f, "cannot cd .. from /"),
+            TranscriptErrorKind::NotADirectory(s) => write!(f, "{s:?} is not a directory"),
+            TranscriptErrorKind::KindChanged(s) => {
+                write!(f, "{s:?} was listed as a file and a directory")
+            }
+            TranscriptErrorKind::InvalidName(s) => write!(f, "invalid name {s:?}"),
+            TranscriptErrorKind::InvalidEntry(s) => write!(f, "invalid ls entry {s:?}"),
+            TranscriptErrorKind::UnexpectedOutput(s) => write!(f, "unexpected output {s:?}"),
+        }
+    }
+}
+
+fn check_name(name: &str) -> Result<&str, TranscriptErrorKind> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        Err(TranscriptErrorKind::InvalidName(name.to_string()))
+    } else {
+        Ok(name)
+    }
+}
+
+/// Rebuilds the filesystem explored by a transcript of `cd` and `ls` commands.
+///
+/// A directory can be listed several times, and `cd` into a directory that was not
+/// listed yet creates it.
+pub fn parse_transcript(input: &str) -> Result<Filesystem, TranscriptError> {
+    let mut filesystem = Filesystem::new();
+    let mut current = Filesystem::ROOT;
+    let mut listing = false;
+
+    // `lines` already drops the `\r` of `\r\n`, other whitespace can be part of a name
+    for (i, line) in input.lines().enumerate() {
+        let error = |kind| TranscriptError { line: i + 1, kind };
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('$') {
+            listing = false;
+            if RE_LS.is_match(line) {
+                listing = true;
+            } else if let Some(cap) = RE_CD.captures(line) {
+                current = cd(&mut filesystem, current, &cap["name"]).map_err(error)?;
+            } else {
+                return Err(error(TranscriptErrorKind::UnknownCommand(line.to_string())));
+            }
+        } else if !listing {
+            return Err(error(TranscriptErrorKind::UnexpectedOutput(
+                line.to_string(),
+            )));
+        } else {
+            add_entry(&mut filesystem, current, line).map_err(error)?;
+        }
+    }
+    Ok(filesystem)
+}
+
+fn cd(
+    filesystem: &mut Filesystem,
+    current: NodeId,
+    name: &str,
+) -> Result<NodeId, TranscriptErrorKind> {
+    match name {
+        "/" => Ok(Filesystem::ROOT),
+        ".." => filesystem
+            .node(current)
+            .parent()
+            .ok_or(TranscriptErrorKind::CdAboveRoot),
+        _ => {
+            let name = check_name(name)?;
+            match filesystem.child(current, name) {
+                Some(id) if !filesystem.node(id).is_dir() => {
+                    Err(TranscriptErrorKind::NotADirectory(name.to_string()))
+                }
+                _ => Ok(filesystem.add_dir(current, name)),
+            }
+        }
+    }
+}
+
+fn add_entry(
+    filesystem: &mut Filesystem,
+    current: NodeId,
+    line: &str,
+) -> Result<(), TranscriptErrorKind> {
+    let (name, kind, size) = if let Some(cap) = RE_DIR.captures(line) {
+        (cap.name("name").unwrap().as_str(), NodeKind::Dir, 0)
+    } else if let Some(cap) = RE_FILE.captures(line) {
+        let size = cap["size"]
+            .parse()
+            .map_err(|_| TranscriptErrorKind::InvalidEntry(line.to_string()))?;
+        (cap.name("name").unwrap().as_str(), NodeKind::File, size)
+    } else {
+        return Err(TranscriptErrorKind::InvalidEntry(line.to_string()));
+    };
+
+    let name = check_name(name)?;
+    if let Some(id) = filesystem.child(current, name) {
+        if filesystem.node(id).kind != kind {
+            return Err(TranscriptErrorKind::KindChanged(name.to_string()));
+        }
+    }
+    match kind {
+        NodeKind::Dir => filesystem.add_dir(current, name),
+        NodeKind::File => filesystem.add_file(current, name, size),
+    };
+    Ok(())
+}
+
+/// The shortest transcript exploring the whole filesystem: `cd` into every
+/// directory in name order and `ls` it.
+pub fn write_transcript(filesystem: &Filesystem) -> String {
+    fn visit(filesystem: &Filesystem, id: NodeId, out: &mut String) {
+        out.push_str("$ ls\n");
+        for child in filesystem.node(id).children() {
+            let node = filesystem.node(child);
+            match node.kind {
+                NodeKind::Dir => out.push_str(&format!("dir {}\n", node.name)),
+                NodeKind::File => out.push_str(&format!("{} {}\n", node.size, node.name)),
+            }
+        }
+        for child in filesystem.dirs_in(id) {
+            out.push_str(&format!("$ cd {}\n", filesystem.node(child).name));
+            visit(filesystem, child, out);
+            out.push_str("$ cd ..\n");
+        }
+    }
+
+    let mut out = "$ cd /\n".to_string();
+    visit(filesystem, Filesystem::ROOT, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn error(input: &str) -> TranscriptError {
+        parse_transcript(input).unwrap_err()
+    }
+
+    fn size(filesystem: &Filesystem, names: &[&str]) -> usize {
+        let id = names.iter().fold(Filesystem::ROOT, |id, name| {
+            filesystem.child(id, name).unwrap()
+        });
+        filesystem.node(id).total_size()
+    }
+
+    #[test]
+    fn test_parse_transcript() {
+        let filesystem = parse_transcript(include_str!("test.txt")).unwrap();
+        assert_eq!(size(&filesystem, &["a", "e"]), 584);
+        assert_eq!(size(&filesystem, &["a"]), 94853);
+        assert_eq!(size(&filesystem, &["d"]), 24933642);
+        assert_eq!(size(&filesystem, &[]), 48381165);
+    }
+
+    #[test]
+    fn test_names_and_repeated_listing() {
+        let input = "$ cd /\n$ ls\ndir My Docs\n0 empty.txt\n$ cd My Docs\n$ ls\n10 a b\n$ ls\n10 a b\n5 Ça\n$ cd ..\n$ ls\ndir My Docs\n0 empty.txt\n";
+        let filesystem = parse_transcript(input).unwrap();
+        assert_eq!(size(&filesystem, &["My Docs"]), 15);
+        assert_eq!(size(&filesystem, &[]), 15);
+        let empty = filesystem.child(Filesystem::ROOT, "empty.txt").unwrap();
+        assert_eq!(filesystem.node(empty).kind, NodeKind::File);
+    }
+
+    #[test]
+    fn test_trailing_spaces() {
+        let input = "$ cd /\r\n$ ls\r\ndir b \r\n10 a \r\n$ cd b \r\n$ ls\r\n1 c\r\n";
+        let filesystem = parse_transcript(input).unwrap();
+        assert_eq!(size(&filesystem, &["b "]), 1);
+        assert!(filesystem.lookup("/a ").is_some());
+        assert_eq!(filesystem.lookup("/a"), None);
+        assert_eq!(
+            write_transcript(&filesystem),
+            "$ cd /\n$ ls\n10 a \ndir b \n$ cd b \n$ ls\n1 c\n$ cd ..\n"
+        );
+    }
+
+    #[test]
+    fn test_cd_unknown_directory() {
+        let filesystem = parse_transcript("$ cd a\n$ cd b\n$ ls\n7 c\n$ cd /\n").unwrap();
+        assert_eq!(size(&filesystem, &["a", "b"]), 7);
+        assert_eq!(size(&filesystem, &[]), 7);
+    }
+
+    #[test]
+    fn test_write_transcript() {
+        let input = "$ cd a\n$ cd b\n$ ls\n7 c\n$ cd /\n$ ls\n0 z\n";
+        let transcript = write_transcript(&parse_transcript(input).unwrap());
+        assert_eq!(
+            transcript,
+            "$ cd /\n$ ls\ndir a\n0 z\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\n7 c\n$ cd ..\n$ cd ..\n"
+        );
+        let filesystem = parse_transcript(include_str!("test.txt")).unwrap();
+        assert_eq!(
+            write_transcript(&parse_transcript(&write_transcript(&filesystem)).unwrap()),
+            write_transcript(&filesystem)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("$ cd /\n$ cd ..\n"),
+            TranscriptError {
+                line: 2,
+                kind: TranscriptErrorKind::CdAboveRoot
+            }
+        );
+        assert_eq!(
+            error("$ ls\n1 a\n$ cd a\n").kind,
+            TranscriptErrorKind::NotADirectory("a".to_string())
+        );
+        assert_eq!(
+            error("$ ls\n1 a\n$ ls\ndir a\n").kind,
+            TranscriptErrorKind::KindChanged("a".to_string())
+        );
+        assert_eq!(
+            error("$ ls\ndir a/b\n").kind,
+            TranscriptErrorKind::InvalidName("a/b".to_string())
+        );
+        assert_eq!(
+            error("$ ls\n-1 a\n").kind,
+            TranscriptErrorKind::InvalidEntry("-1 a".to_string())
+        );
+        assert_eq!(
+            error("$ cd a\n1 b\n"),
+            TranscriptError {
+                line: 2,
+                kind: TranscriptErrorKind::UnexpectedOutput("1 b".to_string())
+            }
+        );
+        assert_eq!(
+            error("$ rm -rf /\n").kind,
+            TranscriptErrorKind::UnknownCommand("$ rm -rf /".to_string())
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! The first versions of both parts, scanning every line four times and walking from
+//! every tree, timed against the monotonic stacks.
+
+use crate::{part1, part2, Grid, Tree};
+use std::time::Instant;
+
+fn part1_naive(grid: &mut Grid) -> i32 {
+    let mut visible = 0;
+    // count how many trees are visible in each line
+    // left to right
+    for line in &mut *grid {
+        let mut visibility_level: i32 = -1;
+        for tree in &mut *line {
+            if tree.height > visibility_level {
+                visibility_level = tree.height;
+                if !tree.visible {
+                    tree.visible = true;
+                    visible += 1;
+                }
+            }
+        }
+    }
+    // right to left
+    for line in &mut *grid {
+        let mut visibility_level = -1;
+        for tree in line.iter_mut().rev() {
+            if tree.height > visibility_level {
+                visibility_level = tree.height;
+                if !tree.visible {
+                    tree.visible = true;
+                    visible += 1;
+                }
+            }
+        }
+    }
+    // up to down
+    for i in 0..grid[0].len() {
+        let mut visibility_level: i32 = -1;
+        for line in grid.iter_mut() {
+            let tree = &mut line[i];
+            if tree.height > visibility_level {
+                visibility_level = tree.height;
+                if !tree.visible {
+                    tree.visible = true;
+                    visible += 1;
+                }
+            }
+        }
+    }
+    // down to up
+    for i in 0..grid[0].len() {
+        let mut visibility_level: i32 = -1;
+        for j in (0..grid.len()).rev() {
+            let tree = &mut grid[j][i];
+            if tree.height > visibility_level {
+                visibility_level = tree.height;
+                if !tree.visible {
+                    tree.visible = true;
+                    visible += 1;
+                }
+            }
+        }
+    }
+
+    visible
+}
+
+fn part2_naive(grid: &mut Grid) -> usize {
+    for row in 0..grid.len() {
+        for col in 0..grid[row].len() {
+            let tree = &grid[row][col];
+
+            // go right
+            let mut score_right = 0;
+            for next_tree in &grid[row][col + 1..] {
+                score_right += 1;
+                if next_tree.height >= tree.height {
+                    break;
+                }
+            }
+
+            // go left
+            let mut score_left = 0;
+            for next_tree in grid[row][..col].iter().rev() {
+                score_left += 1;
+                if next_tree.height >= tree.height {
+                    break;
+                }
+            }
+
+            // go down
+            let mut score_down = 0;
+            for line in &grid[row + 1..] {
+                let next_tree = &line[col];
+                score_down += 1;
+                if next_tree.height >= tree.height {
+                    break;
+                }
+            }
+
+            // go up
+            let mut score_up = 0;
+            for line in grid[..row].iter().rev() {
+                let next_tree = &line[col];
+                score_up += 1;
+                if next_tree.height >= tree.height {
+                    break;
+                }
+            }
+
+            grid[row][col].scenic_score = score_left * score_right * score_down * score_up;
+        }
+    }
+
+    grid.iter()
+        .flatten()
+        .map(|tree| tree.scenic_score)
+        .max()
+        .unwrap()
+}
+
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// A forest of random heights, `width` trees wide and `height` trees high.
+fn generate_forest(width: usize, height: usize) -> Grid {
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    generate_grid(width, height, |_, _| (next_random(&mut state) % 10) as i32)
+}
+
+/// A forest getting taller to the bottom right, so every tree sees
+/// as far as the top and left edges. Heights go over 9, which the walk from every tree
+/// handles in O(width + height) per tree.
+fn generate_slope(width: usize, height: usize) -> Grid {
+    generate_grid(width, height, |row, col| (row + col) as i32)
+}
+
+fn generate_grid(
+    width: usize,
+    height: usize,
+    mut tree_height: impl FnMut(usize, usize) -> i32,
+) -> Grid {
+    (0..height)
+        .map(|row| {
+            (0..width)
+                .map(|col| Tree {
+                    height: tree_height(row, col),
+                    visible: false,
+                    scenic_score: 0,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn bench(size: usize) {
+    for (name, mut grid) in [
+        ("random", generate_forest(size, size)),
+        ("slope", generate_slope(size, size)),
+    ] {
+        println!("{size}x{size} trees, {name}");
+
+        let start = Instant::now();
+        let visible = part1_naive(&mut grid);
+        println!("visible naive:       {visible} {:?}", start.elapsed());
+        let start = Instant::now();
+        let visible = part1(&mut grid);
+        println!("visible stacks:      {visible} {:?}", start.elapsed());
+
+        let start = Instant::now();
+        let score = part2_naive(&mut grid);
+        println!("scenic score naive:  {score} {:?}", start.elapsed());
+        let start = Instant::now();
+        let score = part2(&mut grid);
+        println!("scenic score stacks: {score} {:?}", start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_naive() {
+        let mut grid = parse_input("30373\n25512\n65332\n33549\n35390").unwrap();
+        assert_eq!(part1_naive(&mut grid), 21);
+        assert_eq!(part2_naive(&mut grid), 8);
+    }
+
+    #[test]
+    fn test_generated_forest() {
+        for (width, height) in [(50, 50), (60, 7), (3, 40), (1, 5), (5, 1)] {
+            let mut naive = generate_forest(width, height);
+            let mut stacks = generate_forest(width, height);
+            assert_eq!(part1(&mut stacks), part1_naive(&mut naive));
+            assert_eq!(part2(&mut stacks), part2_naive(&mut naive));
+            assert_eq!(stacks, naive);
+
+            let mut naive = generate_slope(width, height);
+            let mut stacks = generate_slope(width, height);
+            assert_eq!(part1(&mut stacks), part1_naive(&mut naive));
+            assert_eq!(part2(&mut stacks), part2_naive(&mut naive));
+            assert_eq!(stacks, naive);
+        }
+    }
+}
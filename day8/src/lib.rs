@@ -0,0 +1,2 @@
+pub mod sight;
+pub mod viewshed;
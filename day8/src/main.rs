@@ -1,11 +1,15 @@
 use colored::Colorize;
+use day8::sight::views;
+use day8::viewshed::{best_treehouse, render_viewshed, visible_from, Observer};
 use std::fmt::Display;
 
+mod bench;
+
 #[derive(Debug, PartialEq, Eq)]
 struct Tree {
     height: i32,
     visible: bool,
-    scenic_score: i32,
+    scenic_score: usize,
 }
 
 impl Display for Tree {
@@ -29,134 +33,108 @@ fn visualize(grid: &Grid) {
     }
 }
 
-fn parse_input(input: &str) -> Grid {
-    input
-        .lines()
-        .map(|line| {
-            line.to_string()
-                .chars()
-                .filter_map(|c| c.to_digit(10))
-                .map(|x| Tree {
-                    height: x as i32,
-                    visible: false,
-                    scenic_score: 0,
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseErrorKind {
+    Empty,
+    InvalidHeight(char),
+    /// A row with a different number of trees than the first one.
+    RaggedRow {
+        expected: usize,
+        found: usize,
+    },
 }
 
-fn part1(grid: &mut Grid) -> i32 {
-    let mut visible = 0;
-    // count how many trees are visible in each line
-    // left to right
-    for line in &mut *grid {
-        let mut visibility_level: i32 = -1;
-        for tree in &mut *line {
-            if tree.height > visibility_level {
-                visibility_level = tree.height;
-                if !tree.visible {
-                    tree.visible = true;
-                    visible += 1;
-                }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ParseError {
+    /// One-based.
+    line: usize,
+    /// One-based.
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseErrorKind::Empty => write!(f, "no trees"),
+            ParseErrorKind::InvalidHeight(c) => write!(f, "invalid height {c:?}"),
+            ParseErrorKind::RaggedRow { expected, found } => {
+                write!(f, "expected {expected} trees, found {found}")
             }
         }
     }
-    // right to left
-    for line in &mut *grid {
-        let mut visibility_level = -1;
-        for tree in (&mut *line).into_iter().rev() {
-            if tree.height > visibility_level {
-                visibility_level = tree.height;
-                if !tree.visible {
-                    tree.visible = true;
-                    visible += 1;
-                }
-            }
+}
+
+/// Parses a rectangular grid of digits.
+fn parse_input(input: &str) -> Result<Grid, ParseError> {
+    let mut grid: Grid = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let error = |column, kind| ParseError {
+            line: i + 1,
+            column,
+            kind,
+        };
+        let line = line
+            .chars()
+            .enumerate()
+            .map(|(j, c)| match c.to_digit(10) {
+                Some(x) => Ok(Tree {
+                    height: x as i32,
+                    visible: false,
+                    scenic_score: 0,
+                }),
+                None => Err(error(j + 1, ParseErrorKind::InvalidHeight(c))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected = grid.first().map_or(line.len(), |x| x.len());
+        if line.is_empty() && grid.is_empty() {
+            return Err(error(1, ParseErrorKind::Empty));
         }
-    }
-    // up to down
-    for i in 0..grid[0].len() {
-        let mut visibility_level: i32 = -1;
-        for j in 0..grid.len() {
-            let tree = &mut grid[j][i];
-            if tree.height > visibility_level {
-                visibility_level = tree.height;
-                if !tree.visible {
-                    tree.visible = true;
-                    visible += 1;
-                }
-            }
+        if line.is_empty() || line.len() != expected {
+            return Err(error(
+                line.len().min(expected) + 1,
+                ParseErrorKind::RaggedRow {
+                    expected,
+                    found: line.len(),
+                },
+            ));
         }
+        grid.push(line);
     }
-    // down to up
-    for i in 0..grid[0].len() {
-        let mut visibility_level: i32 = -1;
-        for j in (0..grid.len()).rev() {
-            let tree = &mut grid[j][i];
-            if tree.height > visibility_level {
-                visibility_level = tree.height;
-                if !tree.visible {
-                    tree.visible = true;
-                    visible += 1;
-                }
-            }
-        }
+    if grid.is_empty() {
+        return Err(ParseError {
+            line: 1,
+            column: 1,
+            kind: ParseErrorKind::Empty,
+        });
     }
-
-    visible
+    Ok(grid)
 }
 
-// calculate scenic scores
-fn part2(grid: &mut Grid) -> i32 {
-    for row in 0..grid.len() {
-        for col in 0..grid[row].len() {
-            let tree = &grid[row][col];
-
-            // go right
-            let mut score_right = 0;
-            for i in col + 1..grid.len() {
-                let next_tree = &grid[row][i];
-                score_right += 1;
-                if next_tree.height >= tree.height {
-                    break;
-                }
-            }
-
-            // go left
-            let mut score_left = 0;
-            for i in (0..col).rev() {
-                let next_tree = &grid[row][i];
-                score_left += 1;
-                if next_tree.height >= tree.height {
-                    break;
-                }
-            }
-
-            // go down
-            let mut score_down = 0;
-            for i in row + 1..grid[row].len() {
-                let next_tree = &grid[i][col];
-                score_down += 1;
-                if next_tree.height >= tree.height {
-                    break;
-                }
-            }
-
-            // go up
-            let mut score_up = 0;
-            for i in (0..row).rev() {
-                let next_tree = &grid[i][col];
-                score_up += 1;
-                if next_tree.height >= tree.height {
-                    break;
-                }
-            }
+fn heights(grid: &Grid) -> Vec<Vec<i32>> {
+    grid.iter()
+        .map(|line| line.iter().map(|tree| tree.height).collect())
+        .collect()
+}
 
-            grid[row][col].scenic_score = score_left * score_right * score_down * score_up;
+fn part1(grid: &mut Grid) -> i32 {
+    let views = views(&heights(grid));
+    for (line, visible) in grid.iter_mut().zip(views.visible) {
+        for (tree, visible) in line.iter_mut().zip(visible) {
+            tree.visible = visible;
         }
     }
+    grid.iter().flatten().filter(|tree| tree.visible).count() as i32
+}
 
+fn part2(grid: &mut Grid) -> usize {
+    let views = views(&heights(grid));
+    for (line, scores) in grid.iter_mut().zip(views.scenic_score) {
+        for (tree, score) in line.iter_mut().zip(scores) {
+            tree.scenic_score = score;
+        }
+    }
     grid.iter()
         .flatten()
         .map(|tree| tree.scenic_score)
@@ -165,8 +143,61 @@ fn part2(grid: &mut Grid) -> i32 {
 }
 
 fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
     let input = include_str!("input.txt");
-    let mut grid = parse_input(input);
+    let mut grid = match parse_input(input) {
+        Ok(grid) => grid,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+
+    match args.get(1).map(|x| x.as_str()) {
+        Some("bench") => {
+            // cargo run --release -- bench [size]
+            bench::bench(args.get(2).and_then(|x| x.parse().ok()).unwrap_or(1000));
+            return;
+        }
+        Some("view") => {
+            // cargo run -- view <row> <col> [height], outside the forest too
+            let arg = |i: usize| args.get(i).and_then(|x| x.parse::<isize>().ok());
+            let (Some(row), Some(col)) = (arg(2), arg(3)) else {
+                eprintln!("usage: view <row> <col> [height]");
+                return;
+            };
+            let heights = heights(&grid);
+            // on top of the tree there, or at ground level outside the forest
+            let height = args.get(4).and_then(|x| x.parse().ok());
+            let height = height.unwrap_or_else(|| {
+                usize::try_from(row)
+                    .ok()
+                    .zip(usize::try_from(col).ok())
+                    .and_then(|(row, col)| heights.get(row)?.get(col).copied())
+                    .unwrap_or(-1)
+            });
+            let observer = Observer { row, col, height };
+            print!("{}", render_viewshed(&heights, &observer));
+            println!("{} trees visible", visible_from(&heights, &observer).len());
+            return;
+        }
+        Some("treehouse") => {
+            // cargo run -- treehouse
+            let heights = heights(&grid);
+            if let Some(((row, col), seen)) = best_treehouse(&heights) {
+                let observer = Observer {
+                    row: row as isize,
+                    col: col as isize,
+                    height: heights[row][col],
+                };
+                print!("{}", render_viewshed(&heights, &observer));
+                println!("treehouse at row {row}, column {col}: {seen} trees visible");
+            }
+            return;
+        }
+        _ => (),
+    }
+
     let visible = part1(&mut grid);
     visualize(&grid);
     println!("answer 1: {}", visible);
@@ -181,7 +212,7 @@ mod tests {
     #[test]
     fn test_parse_input() {
         assert_eq!(
-            parse_input(TEST_INPUT)[0][0],
+            parse_input(TEST_INPUT).unwrap()[0][0],
             Tree {
                 height: 3,
                 visible: false,
@@ -192,14 +223,76 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let mut grid = parse_input(TEST_INPUT);
+        let mut grid = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part1(&mut grid), 21);
     }
 
     #[test]
     fn test_part2() {
-        let mut grid = parse_input(TEST_INPUT);
+        let mut grid = parse_input(TEST_INPUT).unwrap();
         part1(&mut grid);
         assert_eq!(part2(&mut grid), 8);
     }
+
+    #[test]
+    fn test_rectangular() {
+        let mut wide = parse_input("25512\n65332\n33549").unwrap();
+        assert_eq!(part1(&mut wide), 14);
+        assert_eq!(part2(&mut wide), 3);
+        let mut tall = parse_input("30\n25\n65\n33\n35\n").unwrap();
+        assert_eq!(part1(&mut tall), 10);
+        assert_eq!(part2(&mut tall), 0);
+    }
+
+    #[test]
+    fn test_part2_large_score() {
+        let mut input = vec![vec![b'0'; 1000]; 1000];
+        input[500][500] = b'9';
+        let input = input
+            .iter()
+            .map(|x| String::from_utf8_lossy(x))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut grid = parse_input(&input).unwrap();
+        assert_eq!(part2(&mut grid), 500 * 500 * 499 * 499);
+        assert_eq!(grid[500][500].scenic_score, 62250250000);
+    }
+
+    #[test]
+    fn test_parse_input_invalid() {
+        assert_eq!(
+            parse_input("303\n25x\n653").unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 3,
+                kind: ParseErrorKind::InvalidHeight('x')
+            }
+        );
+        assert_eq!(
+            parse_input("303\n2551\n653").unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 4,
+                kind: ParseErrorKind::RaggedRow {
+                    expected: 3,
+                    found: 4
+                }
+            }
+        );
+        assert_eq!(
+            parse_input("303\n\n653").unwrap_err().kind,
+            ParseErrorKind::RaggedRow {
+                expected: 3,
+                found: 0
+            }
+        );
+        assert_eq!(parse_input("").unwrap_err().kind, ParseErrorKind::Empty);
+    }
+
+    #[test]
+    fn test_all() {
+        let mut grid = parse_input(include_str!("input.txt")).unwrap();
+        assert_eq!(part1(&mut grid), 1801);
+        assert_eq!(part2(&mut grid), 209880);
+    }
 }
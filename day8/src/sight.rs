@@ -0,0 +1,136 @@
+/// What a tree sees looking back along a line of trees, towards where the line starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sight {
+    /// No tree before it is as tall, so it can be seen from the start of the line.
+    pub visible: bool,
+    /// Trees it can see, up to the first one at least as tall or to the edge.
+    pub distance: usize,
+}
+
+/// Trees still in view from the end of a line: those not hidden behind a later,
+/// taller one. Their heights only go down, so once the smaller trees are popped the
+/// first tree at least as tall as a new one is on top, and every tree is pushed and
+/// popped once.
+#[derive(Debug, Clone)]
+struct Stack<T> {
+    trees: Vec<(usize, T)>,
+}
+
+impl<T: Copy + Ord> Stack<T> {
+    fn new() -> Self {
+        Stack { trees: vec![] }
+    }
+
+    /// Adds the tree at `position` in the line, returns what it sees.
+    fn push(&mut self, position: usize, height: T) -> Sight {
+        while self.trees.last().is_some_and(|x| x.1 < height) {
+            self.trees.pop();
+        }
+        let sight = match self.trees.last() {
+            Some(&(j, _)) => Sight {
+                visible: false,
+                distance: position - j,
+            },
+            None => Sight {
+                visible: true,
+                distance: position,
+            },
+        };
+        self.trees.push((position, height));
+        sight
+    }
+}
+
+/// Sights of every tree in a line, in one pass.
+pub fn look_back<T: Copy + Ord>(heights: impl IntoIterator<Item = T>) -> Vec<Sight> {
+    let mut stack = Stack::new();
+    heights
+        .into_iter()
+        .enumerate()
+        .map(|(i, height)| stack.push(i, height))
+        .collect()
+}
+
+/// Visibility from outside the forest and scenic score of every tree, by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Views {
+    pub visible: Vec<Vec<bool>>,
+    pub scenic_score: Vec<Vec<usize>>,
+}
+
+impl Views {
+    fn apply(&mut self, row: usize, col: usize, sight: Sight) {
+        self.visible[row][col] |= sight.visible;
+        self.scenic_score[row][col] *= sight.distance;
+    }
+}
+
+/// Looks along every row and column in both directions, so O(width * height) whatever
+/// the heights. Columns are scanned all at once, a row at a time, to read the grid in
+/// order.
+pub fn views<T: Copy + Ord>(heights: &[Vec<T>]) -> Views {
+    let height = heights.len();
+    let width = heights.first().map_or(0, |x| x.len());
+    let mut views = Views {
+        visible: vec![vec![false; width]; height],
+        scenic_score: vec![vec![1; width]; height],
+    };
+
+    let mut stack = Stack::new();
+    for (row, line) in heights.iter().enumerate() {
+        stack.trees.clear();
+        for (col, &h) in line.iter().enumerate() {
+            views.apply(row, col, stack.push(col, h));
+        }
+        stack.trees.clear();
+        for (k, &h) in line.iter().rev().enumerate() {
+            views.apply(row, width - 1 - k, stack.push(k, h));
+        }
+    }
+
+    let mut stacks = vec![Stack::new(); width];
+    for (row, line) in heights.iter().enumerate() {
+        for (col, &h) in line.iter().enumerate() {
+            views.apply(row, col, stacks[col].push(row, h));
+        }
+    }
+    stacks.iter_mut().for_each(|x| x.trees.clear());
+    for (k, line) in heights.iter().rev().enumerate() {
+        for (col, &h) in line.iter().enumerate() {
+            views.apply(height - 1 - k, col, stacks[col].push(k, h));
+        }
+    }
+    views
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_look_back() {
+        let sights = look_back([3, 0, 3, 7, 3, 5, 4]);
+        assert_eq!(
+            sights.iter().map(|x| x.visible).collect::<Vec<_>>(),
+            vec![true, false, false, true, false, false, false]
+        );
+        assert_eq!(
+            sights.iter().map(|x| x.distance).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 1, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_views() {
+        let heights = ["30373", "25512", "65332", "33549", "35390"]
+            .iter()
+            .map(|x| x.bytes().map(|c| c - b'0').collect())
+            .collect::<Vec<Vec<u8>>>();
+        let views = views(&heights);
+        assert_eq!(views.visible.iter().flatten().filter(|x| **x).count(), 21);
+        assert_eq!(views.scenic_score[1][2], 4);
+        assert_eq!(views.scenic_score[3][2], 8);
+        assert_eq!(views.scenic_score.iter().flatten().max(), Some(&8));
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::Display;
+
+/// Row and column steps of the eight directions, clockwise from up.
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+/// Someone looking at the forest. The position can be outside the forest, and inside
+/// it the tree at the position is the one they stand on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observer<T> {
+    pub row: isize,
+    pub col: isize,
+    /// Eye height, on the same scale as the trees.
+    pub height: T,
+}
+
+/// Steps `k` for which `start + k * step` is in `0..len`, as an inclusive range, in
+/// `i128` so that no position overflows. Unbounded if `step` is 0 and `start` is inside.
+fn steps_inside(start: isize, step: isize, len: usize) -> (i128, i128) {
+    let (start, step, last) = (start as i128, step as i128, len as i128 - 1);
+    if step == 0 {
+        return if (0..=last).contains(&start) {
+            (i128::MIN, i128::MAX)
+        } else {
+            (1, 0)
+        };
+    }
+    let floor = |a: i128| {
+        let (a, b) = if step < 0 { (-a, -step) } else { (a, step) };
+        a.div_euclid(b)
+    };
+    let ceil = |a: i128| -floor(-a);
+    // 0 <= start + k * step <= last
+    if step > 0 {
+        (ceil(-start), floor(last - start))
+    } else {
+        (ceil(last - start), floor(-start))
+    }
+}
+
+/// Trees seen in a straight line along `direction`, in order.
+///
+/// A tree hides the trees behind it that are not taller than it, unless the observer
+/// is taller than it and looks over it. With the observer below every tree this is
+/// the visibility from outside the forest of part 1. Only the steps inside the forest
+/// are looked at, however far the observer is.
+pub fn visible_along<T: Copy + Ord>(
+    heights: &[Vec<T>],
+    observer: &Observer<T>,
+    direction: (isize, isize),
+) -> Vec<(usize, usize)> {
+    if direction == (0, 0) {
+        return vec![];
+    }
+    let rows = heights.len();
+    let cols = heights.first().map_or(0, |x| x.len());
+    let (row_first, row_last) = steps_inside(observer.row, direction.0, rows);
+    let (col_first, col_last) = steps_inside(observer.col, direction.1, cols);
+    let first = row_first.max(col_first).max(1);
+    let last = row_last.min(col_last);
+
+    let mut visible = vec![];
+    // tallest tree so far that the observer cannot look over
+    let mut wall: Option<T> = None;
+    for k in first..=last {
+        // inside the forest, so they fit
+        let row = (observer.row as i128 + k * direction.0 as i128) as usize;
+        let col = (observer.col as i128 + k * direction.1 as i128) as usize;
+        let height = heights[row][col];
+        if wall.is_none_or(|wall| wall < height) {
+            visible.push((row, col));
+        }
+        if height >= observer.height {
+            wall = wall.max(Some(height));
+        }
+    }
+    visible
+}
+
+/// Every tree the observer can see in the eight directions, sorted by position.
+pub fn visible_from<T: Copy + Ord>(
+    heights: &[Vec<T>],
+    observer: &Observer<T>,
+) -> Vec<(usize, usize)> {
+    let mut visible = DIRECTIONS
+        .iter()
+        .flat_map(|&direction| visible_along(heights, observer, direction))
+        .collect::<Vec<_>>();
+    visible.sort_unstable();
+    visible
+}
+
+/// Which trees the observer can see, by row.
+pub fn viewshed<T: Copy + Ord>(heights: &[Vec<T>], observer: &Observer<T>) -> Vec<Vec<bool>> {
+    let mut map = heights
+        .iter()
+        .map(|x| vec![false; x.len()])
+        .collect::<Vec<_>>();
+    for (row, col) in visible_from(heights, observer) {
+        map[row][col] = true;
+    }
+    map
+}
+
+/// The viewshed as text: the observer is `@`, visible trees show their height and
+/// hidden ones are `.`.
+pub fn render_viewshed<T: Copy + Ord + Display>(
+    heights: &[Vec<T>],
+    observer: &Observer<T>,
+) -> String {
+    let map = viewshed(heights, observer);
+    let mut out = String::new();
+    for (row, line) in heights.iter().enumerate() {
+        for (col, height) in line.iter().enumerate() {
+            if (row as isize, col as isize) == (observer.row, observer.col) {
+                out.push('@');
+            } else if map[row][col] {
+                out.push_str(&height.to_string());
+            } else {
+                out.push('.');
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The tree from which the most trees can be seen, standing at its top, with the
+/// number of trees seen. The first one in reading order on ties.
+pub fn best_treehouse<T: Copy + Ord>(heights: &[Vec<T>]) -> Option<((usize, usize), usize)> {
+    let mut best: Option<((usize, usize), usize)> = None;
+    for (row, line) in heights.iter().enumerate() {
+        for (col, &height) in line.iter().enumerate() {
+            let observer = Observer {
+                row: row as isize,
+                col: col as isize,
+                height,
+            };
+            let seen = visible_from(heights, &observer).len();
+            if best.is_none_or(|x| seen > x.1) {
+                best = Some(((row, col), seen));
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn example() -> Vec<Vec<i32>> {
+        ["30373", "25512", "65332", "33549", "35390"]
+            .iter()
+            .map(|x| x.bytes().map(|c| (c - b'0') as i32).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_visible_along() {
+        let heights = example();
+        // from the left of the second row at ground level, as in part 1
+        let observer = Observer {
+            row: 1,
+            col: -1,
+            height: -1,
+        };
+        assert_eq!(
+            visible_along(&heights, &observer, (0, 1)),
+            vec![(1, 0), (1, 1)]
+        );
+        // the same from higher up sees over the first 5
+        let observer = Observer {
+            height: 6,
+            ..observer
+        };
+        assert_eq!(
+            visible_along(&heights, &observer, (0, 1)),
+            vec![(1, 0), (1, 1), (1, 2), (1, 3), (1, 4)]
+        );
+        // diagonally from the top of the middle 3, the 4 hides the 0 behind it
+        let observer = Observer {
+            row: 2,
+            col: 2,
+            height: 3,
+        };
+        assert_eq!(visible_along(&heights, &observer, (1, 1)), vec![(3, 3)]);
+        // but the 3 up and right is still seen behind the 1
+        assert_eq!(
+            visible_along(&heights, &observer, (-1, 1)),
+            vec![(1, 3), (0, 4)]
+        );
+    }
+
+    #[test]
+    fn test_visible_along_far_away() {
+        let heights = example();
+        let observer = Observer {
+            row: isize::MIN,
+            col: 2,
+            height: -1,
+        };
+        assert_eq!(
+            visible_along(&heights, &observer, (1, 0)),
+            vec![(0, 2), (1, 2)]
+        );
+        assert_eq!(visible_along(&heights, &observer, (-1, 0)), vec![]);
+        let observer = Observer {
+            row: -300_000_000,
+            col: -300_000_000,
+            height: -1,
+        };
+        assert_eq!(
+            visible_along(&heights, &observer, (1, 1)),
+            vec![(0, 0), (1, 1)]
+        );
+        let observer = Observer {
+            row: isize::MAX,
+            col: isize::MAX,
+            height: -1,
+        };
+        assert_eq!(visible_along(&heights, &observer, (1, 1)), vec![]);
+        assert_eq!(
+            visible_along(&heights, &observer, (-1, -1)),
+            vec![(4, 4), (3, 3), (1, 1)]
+        );
+        // larger steps skip trees
+        let observer = Observer {
+            row: 0,
+            col: 0,
+            height: 9,
+        };
+        assert_eq!(
+            visible_along(&heights, &observer, (1, 2)),
+            vec![(1, 2), (2, 4)]
+        );
+        assert_eq!(visible_along(&heights, &observer, (0, 0)), vec![]);
+    }
+
+    #[test]
+    fn test_part1_from_outside() {
+        let heights = example();
+        let mut visible = vec![];
+        for i in 0..5 {
+            for (row, col, direction) in [
+                (i, -1, (0, 1)),
+                (i, 5, (0, -1)),
+                (-1, i, (1, 0)),
+                (5, i, (-1, 0)),
+            ] {
+                let observer = Observer {
+                    row,
+                    col,
+                    height: -1,
+                };
+                visible.extend(visible_along(&heights, &observer, direction));
+            }
+        }
+        visible.sort_unstable();
+        visible.dedup();
+        assert_eq!(visible.len(), 21);
+    }
+
+    #[test]
+    fn test_render_viewshed() {
+        let heights = example();
+        let observer = Observer {
+            row: 3,
+            col: 2,
+            height: 5,
+        };
+        assert_eq!(
+            render_viewshed(&heights, &observer),
+            "\
+.....
+..5.2
+.533.
+33@49
+.539.
+"
+        );
+    }
+
+    #[test]
+    fn test_best_treehouse() {
+        assert_eq!(best_treehouse(&example()), Some(((2, 1), 13)));
+        assert_eq!(best_treehouse::<i32>(&[]), None);
+    }
+}